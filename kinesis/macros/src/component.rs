@@ -0,0 +1,360 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt, meta::ParseNestedMeta, spanned::Spanned, Error, Expr, FnArg, ImplItem,
+    ImplItemFn, ItemImpl, Result, ReturnType, Type, Visibility,
+};
+
+/// Name of the attribute used to mark a method as an event handler.
+const EVENT_ATTRIBUTE: &str = "event";
+
+/// Name of the attribute used to mark a method as the props handler.
+const PROPS_ATTRIBUTE: &str = "props";
+
+/// Arguments passed to the attribute, such as `#[component(styles = "...")]`.
+#[derive(Default)]
+pub struct ComponentArgs {
+    /// The stylesheet of the component, which must evaluate to a `&'static str`.
+    styles: Option<Expr>,
+
+    /// The type identifying the fields of the component.
+    fields: Option<Type>,
+}
+
+impl ComponentArgs {
+    /// Parse a single argument, for use with [`syn::meta::parser`].
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("styles") {
+            self.styles = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("fields") {
+            self.fields = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unknown argument, expected `styles` or `fields`"))
+        }
+    }
+}
+
+/// A method that has been marked as an event handler.
+struct EventHandler {
+    /// Visibility of the method, which is re-used for the generated constant.
+    visibility: Visibility,
+
+    /// The constant containing the event id (eg `ON_INCREMENT`).
+    constant: syn::Ident,
+
+    /// The type of event taken by the method.
+    event: TokenStream,
+
+    /// The call to the method when the event is received.
+    call: TokenStream,
+}
+
+/// The method that has been marked as the props handler.
+struct PropsHandler {
+    /// The type of the props, which is taken by reference.
+    ty: Type,
+
+    /// The call to the method when new props are received.
+    call: TokenStream,
+}
+
+/// Generate an implementation of `Component` for the type of the impl block, dispatching events
+/// to each method marked with `#[event]`, and props to the method marked with `#[props]`.
+pub fn expand(mut item: ItemImpl, args: ComponentArgs) -> Result<TokenStream> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(Error::new(
+            path.span(),
+            "`#[component]` must be used on an inherent impl block",
+        ));
+    }
+
+    let mut handlers = Vec::new();
+    let mut props_handler = None;
+    for impl_item in item.items.iter_mut() {
+        if let ImplItem::Fn(method) = impl_item {
+            if take_attribute(method, EVENT_ATTRIBUTE) {
+                handlers.push(event_handler(method)?);
+            }
+
+            if take_attribute(method, PROPS_ATTRIBUTE) {
+                if props_handler.is_some() {
+                    return Err(Error::new(
+                        method.sig.ident.span(),
+                        "only one method may be marked with `#[props]`",
+                    ));
+                }
+
+                props_handler = Some(props_handler_for(method)?);
+            }
+        }
+    }
+
+    let constants = handlers.iter().enumerate().map(
+        |(
+            event_id,
+            EventHandler {
+                visibility,
+                constant,
+                event,
+                ..
+            },
+        )| {
+            quote! {
+                #visibility const #constant: ::kinesis::event::Handler<#event> =
+                    ::kinesis::event::Handler::new(#event_id);
+            }
+        },
+    );
+
+    let arms = handlers
+        .iter()
+        .enumerate()
+        .map(|(event_id, EventHandler { call, .. })| {
+            quote! {
+                #event_id => #call,
+            }
+        });
+
+    let (props_ty, props_call) = match props_handler {
+        Some(PropsHandler { ty, call }) => (quote!(#ty), call),
+        None => (quote!(()), quote!(::std::option::Option::None)),
+    };
+
+    let fields = match args.fields {
+        Some(fields) => quote!(#fields),
+        None => quote!(usize),
+    };
+
+    let styles = args.styles.map(|styles| {
+        quote! {
+            fn styles(&self) -> ::std::option::Option<&'static str> {
+                ::std::option::Option::Some(#styles)
+            }
+        }
+    });
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics #self_ty #where_clause {
+            #(#constants)*
+        }
+
+        impl #impl_generics ::kinesis::component::Component for #self_ty #where_clause {
+            type Props = #props_ty;
+            type Field = #fields;
+
+            #[allow(unused_variables)]
+            fn handle_event(
+                &mut self,
+                event_id: usize,
+                event: ::kinesis::event::EventPayload,
+                context: &::kinesis::event::EventContext,
+            ) -> ::std::result::Result<
+                ::std::option::Option<::std::vec::Vec<Self::Field>>,
+                ::kinesis::Error,
+            > {
+                match event_id {
+                    #(#arms)*
+                    _ => ::std::result::Result::Ok(::std::option::Option::None),
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn handle_props(
+                &mut self,
+                props: &Self::Props,
+                changed: &[usize],
+            ) -> ::std::option::Option<::std::vec::Vec<Self::Field>> {
+                #props_call
+            }
+
+            #styles
+        }
+    })
+}
+
+/// Remove the attribute with the provided name from a method, returning whether it was present.
+fn take_attribute(method: &mut ImplItemFn, name: &str) -> bool {
+    let attribute_count = method.attrs.len();
+    method
+        .attrs
+        .retain(|attribute| !attribute.path().is_ident(name));
+
+    method.attrs.len() != attribute_count
+}
+
+/// Validate the signature of the props handler, and generate the call to it. The handler must
+/// take `&mut self` and a reference to the props, and may optionally take the changed fields of
+/// the props. If the handler doesn't return anything, it is treated as not changing any fields.
+fn props_handler_for(method: &ImplItemFn) -> Result<PropsHandler> {
+    let signature = &method.sig;
+    let name = &signature.ident;
+
+    let mut inputs = signature.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_some() => {}
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "the props handler must take `&mut self` as its first argument",
+            ))
+        }
+    }
+
+    let ty = match inputs.next() {
+        Some(FnArg::Typed(argument)) => match argument.ty.as_ref() {
+            Type::Reference(reference) if reference.mutability.is_none() => {
+                reference.elem.as_ref().clone()
+            }
+            ty => {
+                return Err(Error::new(
+                    ty.span(),
+                    "the props handler must take the props by reference",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "the props handler must take the props as an argument",
+            ))
+        }
+    };
+
+    let arguments = match (inputs.next(), inputs.next()) {
+        (None, _) => quote!(props),
+        (Some(_), None) => quote!(props, changed),
+        (Some(_), Some(extra)) => {
+            return Err(Error::new(
+                extra.span(),
+                "the props handler may only take the props and the changed fields as arguments",
+            ))
+        }
+    };
+
+    let call = match signature.output {
+        ReturnType::Default => quote_spanned! { name.span() =>
+            {
+                self.#name(#arguments);
+                ::std::option::Option::None
+            }
+        },
+        ReturnType::Type(..) => quote_spanned! { name.span() =>
+            self.#name(#arguments)
+        },
+    };
+
+    Ok(PropsHandler { ty, call })
+}
+
+/// Validate the signature of an event handler, and generate the call to it. Handlers must take
+/// `&mut self`, and may optionally take the DOM event (which is cast to the type that it takes),
+/// followed by a `Spawner` for the component. If the handler doesn't return anything, it is
+/// treated as not changing any fields.
+fn event_handler(method: &ImplItemFn) -> Result<EventHandler> {
+    let signature = &method.sig;
+    let name = &signature.ident;
+
+    let mut inputs = signature.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_some() => {}
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "event handlers must take `&mut self` as their first argument",
+            ))
+        }
+    }
+
+    let constant = format_ident!(
+        "{}",
+        name.unraw().to_string().to_uppercase(),
+        span = name.span()
+    );
+
+    let mut event = None;
+    let mut spawner = false;
+    for argument in inputs {
+        match argument {
+            FnArg::Typed(argument) if !spawner && is_spawner(&argument.ty) => spawner = true,
+            FnArg::Typed(argument) if !spawner && event.is_none() => event = Some(&argument.ty),
+            argument => {
+                return Err(Error::new(
+                    argument.span(),
+                    "event handlers may only take the event and a `Spawner` as arguments",
+                ))
+            }
+        }
+    }
+
+    // Handlers taking a DOM event fail for events without one
+    let (event, cast, event_argument) = match event {
+        Some(ty) => (
+            quote!(#ty),
+            quote!(let event = Self::#constant.cast(event)?;),
+            Some(quote!(event)),
+        ),
+        None => (quote!(::kinesis::event::Event), quote!(), None),
+    };
+
+    let (spawn, spawner_argument) = if spawner {
+        (
+            quote! {
+                let spawner = context
+                    .spawner::<Self>()
+                    .ok_or(::kinesis::Error::UnboundController)?;
+            },
+            Some(quote!(spawner)),
+        )
+    } else {
+        (quote!(), None)
+    };
+
+    let arguments = event_argument.into_iter().chain(spawner_argument);
+    let arguments = quote!(#(#arguments),*);
+
+    let call = match signature.output {
+        ReturnType::Default => quote_spanned! { name.span() =>
+            {
+                #cast
+                #spawn
+                self.#name(#arguments);
+                ::std::result::Result::Ok(::std::option::Option::None)
+            }
+        },
+        ReturnType::Type(..) => quote_spanned! { name.span() =>
+            {
+                #cast
+                #spawn
+                ::std::result::Result::Ok(self.#name(#arguments))
+            }
+        },
+    };
+
+    Ok(EventHandler {
+        visibility: method.vis.clone(),
+        constant,
+        event,
+        call,
+    })
+}
+
+/// Check whether an argument takes a `Spawner`, which is passed the spawner for the component.
+fn is_spawner(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Spawner"),
+        _ => false,
+    }
+}
@@ -0,0 +1,52 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result};
+
+/// Generate an implementation of `Field` for an enum, identifying each variant by its index in the
+/// order that they are declared.
+pub fn expand(item: DeriveInput) -> Result<TokenStream> {
+    let Data::Enum(data) = &item.data else {
+        return Err(Error::new(
+            item.ident.span(),
+            "`Field` can only be derived for enums",
+        ));
+    };
+
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new(
+            variant.ident.span(),
+            "`Field` can only be derived for enums without any fields",
+        ));
+    }
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let indexes = (0..variants.len()).collect::<Vec<_>>();
+
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::kinesis::component::Field for #ident #ty_generics #where_clause {
+            fn index(self) -> usize {
+                match self {
+                    #(Self::#variants => #indexes,)*
+                }
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#indexes => ::std::option::Option::Some(Self::#variants),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
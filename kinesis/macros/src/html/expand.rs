@@ -0,0 +1,190 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+
+use super::parse::{Attribute, DynamicBlock, Element, Markup, MarkupNode, SlotBlock};
+
+/// Expand the parsed markup into a chain of `FragmentBuilder` calls.
+pub fn expand(markup: Markup) -> TokenStream {
+    let mut expander = Expander::default();
+    expander.expand_nodes(&markup.nodes, None);
+
+    let calls = expander.calls;
+    quote! {
+        ::kinesis::fragment::Fragment::build() #(#calls)*
+    }
+}
+
+/// Tracks the builder calls that have been generated, as well as the index of the next static
+/// node, which is used as the `location` for any children of that node.
+#[derive(Default)]
+struct Expander {
+    calls: Vec<TokenStream>,
+    next_index: usize,
+}
+
+impl Expander {
+    fn expand_nodes(&mut self, nodes: &[MarkupNode], parent: Option<usize>) {
+        for node in nodes {
+            self.expand_node(node, parent);
+        }
+    }
+
+    fn expand_node(&mut self, node: &MarkupNode, parent: Option<usize>) {
+        let location = location(parent);
+
+        match node {
+            MarkupNode::Element(element) => {
+                let index = self.next_static_index();
+                self.calls.push(expand_element(element, location));
+
+                // Attributes and bindings target the element once it has been added. Bindings
+                // are added after the children, so that the value of a `<select>` is applied once
+                // its options exist.
+                let mut bindings = Vec::new();
+                for attribute in &element.attributes {
+                    match attribute {
+                        Attribute::Static { name, value } => {
+                            let name = &name.value;
+                            self.calls.push(quote! {
+                                .with_attribute(
+                                    #index,
+                                    #name,
+                                    ::std::string::ToString::to_string(&(#value)),
+                                )
+                            });
+                        }
+                        Attribute::Dynamic {
+                            name,
+                            dependencies,
+                            get_value,
+                        } => {
+                            let name = &name.value;
+                            self.calls.push(quote! {
+                                .with_dynamic_attribute(#dependencies, #index, #name, #get_value)
+                            });
+                        }
+                        Attribute::Binding {
+                            property,
+                            id,
+                            get_value,
+                            set_value,
+                        } => {
+                            let method = format_ident!(
+                                "with_{}_binding",
+                                property.value,
+                                span = property.span
+                            );
+                            bindings.push(quote! {
+                                .#method(#id, #index, #get_value, #set_value)
+                            });
+                        }
+                        _ => (),
+                    }
+                }
+
+                self.expand_nodes(&element.children, Some(index));
+                self.calls.extend(bindings);
+            }
+            MarkupNode::Text(text) => {
+                self.next_static_index();
+                self.calls.push(quote! {
+                    .with_text(#text, #location)
+                });
+            }
+            MarkupNode::Expression(expression) => {
+                self.next_static_index();
+                self.calls.push(quote! {
+                    .with_text(::std::string::ToString::to_string(&(#expression)), #location)
+                });
+            }
+            MarkupNode::Dynamic(DynamicBlock {
+                name,
+                dependencies,
+                arguments,
+            }) => {
+                let method = format_ident!("with_{}", name, span = name.span());
+                self.calls.push(quote_spanned! { name.span() =>
+                    .#method(#dependencies, #location, #(#arguments),*)
+                });
+            }
+            MarkupNode::Slot(SlotBlock { name }) => {
+                self.calls.push(match name {
+                    Some(name) => quote! {
+                        .with_slot(#name, #location)
+                    },
+                    None => quote! {
+                        .with_default_slot(#location)
+                    },
+                });
+            }
+        }
+    }
+
+    /// Reserve the index for a static node, in the order that it will be added to the builder.
+    fn next_static_index(&mut self) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        index
+    }
+}
+
+fn expand_element(element: &Element, location: TokenStream) -> TokenStream {
+    let name = &element.name.value;
+
+    let events = element
+        .attributes
+        .iter()
+        .filter_map(|attribute| match attribute {
+            Attribute::Event {
+                event_type,
+                modifiers,
+                event_id,
+            } => {
+                // Refer to the kind by name, so that an unknown event will fail to compile
+                let kind = format_ident!(
+                    "{}",
+                    event_type.value.replace('-', "_"),
+                    span = event_type.span
+                );
+                if modifiers.is_empty() {
+                    return Some(quote! {
+                        .with_event(::kinesis::event::on::#kind, #event_id)
+                    });
+                }
+
+                // Each modifier is a method on the options, so an unknown modifier will fail to
+                // compile
+                let options = modifiers.iter().map(|modifier| {
+                    quote_spanned! { modifier.span() =>
+                        .#modifier()
+                    }
+                });
+                Some(quote! {
+                    .with_event_options(
+                        ::kinesis::event::on::#kind,
+                        #event_id,
+                        ::kinesis::event::EventOptions::new() #(#options)*,
+                    )
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if events.is_empty() {
+        return quote! {
+            .with_element(#name, #location)
+        };
+    }
+
+    quote! {
+        .with_node(::kinesis::fragment::Node::element(#name) #(#events)*, #location)
+    }
+}
+
+fn location(parent: Option<usize>) -> TokenStream {
+    match parent {
+        Some(parent) => quote!(::std::option::Option::Some(#parent)),
+        None => quote!(::std::option::Option::None),
+    }
+}
@@ -0,0 +1,5 @@
+mod expand;
+mod parse;
+
+pub use expand::expand;
+pub use parse::Markup;
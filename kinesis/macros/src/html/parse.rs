@@ -0,0 +1,444 @@
+use proc_macro2::Span;
+use syn::{
+    braced,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Error, Expr, ExprLit, Ident, Lit, LitStr, Result, Token,
+};
+
+/// Elements that can never contain children, so are allowed to omit their closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Properties of form elements which can be bound with `bind:property`.
+const BINDING_PROPERTIES: &[&str] = &["value", "checked"];
+
+/// The top level of the macro input, consisting of any number of sibling nodes.
+pub struct Markup {
+    pub nodes: Vec<MarkupNode>,
+}
+
+impl Parse for Markup {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let nodes = parse_children(input)?;
+
+        if !input.is_empty() {
+            // The only way to stop parsing children early is a closing tag
+            let close = input.parse::<Token![<]>()?;
+            input.parse::<Token![/]>()?;
+            let name = input.parse::<TagName>()?;
+
+            return Err(Error::new(
+                close.span,
+                format!("unexpected closing tag `</{}>`", name.value),
+            ));
+        }
+
+        Ok(Self { nodes })
+    }
+}
+
+/// A single item within the markup.
+pub enum MarkupNode {
+    /// An element, such as `<p>...</p>`.
+    Element(Element),
+
+    /// A string literal, rendered as a text node.
+    Text(LitStr),
+
+    /// An expression within braces (eg `{count}`), rendered as a text node.
+    Expression(Expr),
+
+    /// A dynamic block (eg `{#iter(&[0], ...)}`), expanded to the matching builder method.
+    Dynamic(DynamicBlock),
+
+    /// A slot (eg `{#slot("header")}`), where content from the parent component is placed.
+    Slot(SlotBlock),
+}
+
+impl Parse for MarkupNode {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![<]) {
+            Ok(Self::Element(input.parse()?))
+        } else if input.peek(LitStr) {
+            Ok(Self::Text(input.parse()?))
+        } else if input.peek(syn::token::Brace) {
+            let content;
+            let brace = braced!(content in input);
+
+            if content.is_empty() {
+                Err(Error::new(brace.span.join(), "expected an expression"))
+            } else if content.peek(Token![#]) {
+                Ok(match content.parse()? {
+                    Block::Dynamic(block) => Self::Dynamic(block),
+                    Block::Slot(block) => Self::Slot(block),
+                })
+            } else {
+                Ok(Self::Expression(content.parse()?))
+            }
+        } else {
+            Err(input.error("expected an element, a string literal or a `{...}` block"))
+        }
+    }
+}
+
+/// Parse sibling nodes until either the end of the input, or a closing tag is found.
+fn parse_children(input: ParseStream) -> Result<Vec<MarkupNode>> {
+    let mut nodes = Vec::new();
+
+    while !input.is_empty() && !is_closing_tag(input) {
+        nodes.push(input.parse()?);
+    }
+
+    Ok(nodes)
+}
+
+fn is_closing_tag(input: ParseStream) -> bool {
+    input.peek(Token![<]) && input.peek2(Token![/])
+}
+
+/// The name of an element or attribute, which may contain `-` (eg `my-element`).
+pub struct TagName {
+    pub value: String,
+    pub span: Span,
+}
+
+impl Parse for TagName {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let first = Ident::parse_any(input)?;
+
+        let mut value = first.to_string();
+        while input.peek(Token![-]) {
+            input.parse::<Token![-]>()?;
+            value.push('-');
+            value.push_str(&Ident::parse_any(input)?.to_string());
+        }
+
+        Ok(Self {
+            value,
+            span: first.span(),
+        })
+    }
+}
+
+pub struct Element {
+    pub name: TagName,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<MarkupNode>,
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![<]>()?;
+        let name = input.parse::<TagName>()?;
+
+        let mut attributes = Vec::new();
+        while !input.peek(Token![>]) && !input.peek(Token![/]) {
+            if input.is_empty() {
+                return Err(Error::new(
+                    name.span,
+                    format!("unterminated opening tag `<{}`", name.value),
+                ));
+            }
+
+            attributes.push(input.parse()?);
+        }
+
+        // Self closing element
+        if input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            input.parse::<Token![>]>()?;
+
+            return Ok(Self {
+                name,
+                attributes,
+                children: Vec::new(),
+            });
+        }
+
+        input.parse::<Token![>]>()?;
+
+        if VOID_ELEMENTS.contains(&name.value.as_str()) {
+            return Ok(Self {
+                name,
+                attributes,
+                children: Vec::new(),
+            });
+        }
+
+        let children = parse_children(input)?;
+
+        if input.is_empty() {
+            return Err(Error::new(
+                name.span,
+                format!(
+                    "unclosed element `<{}>`, expected `</{}>`",
+                    name.value, name.value
+                ),
+            ));
+        }
+
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        let close = input.parse::<TagName>()?;
+        if close.value != name.value {
+            return Err(Error::new(
+                close.span,
+                format!(
+                    "mismatched closing tag, expected `</{}>` but found `</{}>`",
+                    name.value, close.value
+                ),
+            ));
+        }
+        input.parse::<Token![>]>()?;
+
+        Ok(Self {
+            name,
+            attributes,
+            children,
+        })
+    }
+}
+
+pub enum Attribute {
+    /// An attribute set on the element, written as `name="value"`, `name={value}`, or just `name`
+    /// for an empty value.
+    Static { name: TagName, value: Expr },
+
+    /// An attribute that is recomputed when its dependencies change, written as
+    /// `name={#(dependencies, get_value)}`.
+    Dynamic {
+        name: TagName,
+        dependencies: Expr,
+        get_value: Expr,
+    },
+
+    /// An event listener, written as `on:event={handler}`. Options and modifiers may follow the
+    /// event, such as `on:submit|prevent_default={handler}`.
+    Event {
+        event_type: TagName,
+        modifiers: Vec<Ident>,
+        event_id: Expr,
+    },
+
+    /// Two-way binding of a form property (`value` or `checked`) with a field, written as
+    /// `bind:property={#(id, get_value, set_value)}`.
+    Binding {
+        property: TagName,
+        id: Expr,
+        get_value: Expr,
+        set_value: Expr,
+    },
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse::<TagName>()?;
+
+        if name.value == "on" && input.peek(Token![:]) {
+            input.parse::<Token![:]>()?;
+            let event_type = input.parse::<TagName>()?;
+
+            let mut modifiers = Vec::new();
+            while input.peek(Token![|]) {
+                input.parse::<Token![|]>()?;
+                modifiers.push(Ident::parse_any(input)?);
+            }
+
+            input.parse::<Token![=]>()?;
+            let event_id = parse_attribute_value(input)?;
+
+            Ok(Self::Event {
+                event_type,
+                modifiers,
+                event_id,
+            })
+        } else if name.value == "bind" && input.peek(Token![:]) {
+            input.parse::<Token![:]>()?;
+            let property = input.parse::<TagName>()?;
+
+            if !BINDING_PROPERTIES.contains(&property.value.as_str()) {
+                return Err(Error::new(
+                    property.span,
+                    format!(
+                        "unknown binding `bind:{}`, expected one of `bind:value` or `bind:checked`",
+                        property.value
+                    ),
+                ));
+            }
+
+            input.parse::<Token![=]>()?;
+            parse_binding(input, property)
+        } else if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+
+            if is_dynamic_block(input) {
+                return parse_dynamic_attribute(input, name);
+            }
+
+            let value = parse_attribute_value(input)?;
+
+            Ok(Self::Static { name, value })
+        } else {
+            let value = Expr::Lit(ExprLit {
+                attrs: Vec::new(),
+                lit: Lit::Str(LitStr::new("", name.span)),
+            });
+
+            Ok(Self::Static { name, value })
+        }
+    }
+}
+
+/// Check whether the input is a block starting with `#`, without consuming it.
+fn is_dynamic_block(input: ParseStream) -> bool {
+    let fork = input.fork();
+    let starts_with_hash = || -> Result<bool> {
+        let content;
+        braced!(content in fork);
+        Ok(content.peek(Token![#]))
+    };
+
+    starts_with_hash().unwrap_or(false)
+}
+
+/// Parse the value of a dynamic attribute, in the form of `{#(dependencies, get_value)}`.
+fn parse_dynamic_attribute(input: ParseStream, name: TagName) -> Result<Attribute> {
+    let content;
+    braced!(content in input);
+    content.parse::<Token![#]>()?;
+
+    let arguments;
+    let parens = parenthesized!(arguments in content);
+    let mut arguments = Punctuated::<Expr, Token![,]>::parse_terminated(&arguments)?.into_iter();
+
+    match (arguments.next(), arguments.next(), arguments.next()) {
+        (Some(dependencies), Some(get_value), None) => Ok(Attribute::Dynamic {
+            name,
+            dependencies,
+            get_value,
+        }),
+        _ => Err(Error::new(
+            parens.span.join(),
+            "dynamic attributes require a list of dependencies and a function to get the value",
+        )),
+    }
+}
+
+/// Parse the value of a binding, in the form of `{#(id, get_value, set_value)}`.
+fn parse_binding(input: ParseStream, property: TagName) -> Result<Attribute> {
+    let content;
+    let brace = braced!(content in input);
+
+    if !content.peek(Token![#]) {
+        return Err(Error::new(
+            brace.span.join(),
+            "bindings must be written as `{#(id, get_value, set_value)}`",
+        ));
+    }
+    content.parse::<Token![#]>()?;
+
+    let arguments;
+    let parens = parenthesized!(arguments in content);
+    let mut arguments = Punctuated::<Expr, Token![,]>::parse_terminated(&arguments)?.into_iter();
+
+    match (
+        arguments.next(),
+        arguments.next(),
+        arguments.next(),
+        arguments.next(),
+    ) {
+        (Some(id), Some(get_value), Some(set_value), None) => Ok(Attribute::Binding {
+            property,
+            id,
+            get_value,
+            set_value,
+        }),
+        _ => Err(Error::new(
+            parens.span.join(),
+            "bindings require the id of the bound field, a function to get the value, and a \
+             function to set the value",
+        )),
+    }
+}
+
+/// Parse the value of an attribute, which may either be a literal or an expression within braces.
+fn parse_attribute_value(input: ParseStream) -> Result<Expr> {
+    if input.peek(syn::token::Brace) {
+        let content;
+        braced!(content in input);
+
+        content.parse()
+    } else if input.peek(Lit) {
+        Ok(Expr::Lit(ExprLit {
+            attrs: Vec::new(),
+            lit: input.parse()?,
+        }))
+    } else {
+        Err(input.error("expected a literal or a `{...}` block"))
+    }
+}
+
+/// A dynamic block, written as `{#name(dependencies, arguments...)}`.
+pub struct DynamicBlock {
+    pub name: Ident,
+    pub dependencies: Expr,
+    pub arguments: Vec<Expr>,
+}
+
+/// A slot, written as `{#slot()}` for the default slot or `{#slot(name)}` for a named slot. Unlike
+/// other dynamic blocks, it doesn't take any dependencies.
+pub struct SlotBlock {
+    pub name: Option<Expr>,
+}
+
+/// Either a dynamic block or a slot, as they share the same syntax.
+pub enum Block {
+    Dynamic(DynamicBlock),
+    Slot(SlotBlock),
+}
+
+impl Parse for Block {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![#]>()?;
+        let name = Ident::parse_any(input)?;
+
+        let content;
+        let parens = parenthesized!(content in input);
+        let mut arguments = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?.into_iter();
+
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after dynamic block"));
+        }
+
+        if name == "slot" {
+            let slot_name = arguments.next();
+
+            if arguments.next().is_some() {
+                return Err(Error::new(
+                    parens.span.join(),
+                    "`#slot` only accepts the name of the slot",
+                ));
+            }
+
+            return Ok(Self::Slot(SlotBlock { name: slot_name }));
+        }
+
+        let dependencies = arguments.next().ok_or_else(|| {
+            Error::new(
+                name.span(),
+                format!("`#{name}` requires a list of dependencies as its first argument"),
+            )
+        })?;
+
+        Ok(Self::Dynamic(DynamicBlock {
+            name,
+            dependencies,
+            arguments: arguments.collect(),
+        }))
+    }
+}
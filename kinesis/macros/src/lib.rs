@@ -0,0 +1,100 @@
+mod component;
+mod field;
+mod html;
+mod props;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemImpl};
+
+/// Parse HTML-like syntax into a chain of `FragmentBuilder` calls, computing the `location` of
+/// each item based off of where it appears in the markup.
+///
+/// - Elements are written as `<p>...</p>`, or `<br />` if they have no children.
+/// - Text is written as a string literal, such as `"some content"`.
+/// - `{expr}` renders the result of `expr` as static text, using [`ToString`].
+/// - `name="value"` or `name={expr}` on an element will set the attribute `name`. The attribute
+///   name on its own (eg `<input disabled />`) will set it with an empty value.
+/// - `name={#(dependencies, get_value)}` on an element will set the attribute `name` to the result
+///   of `get_value` whenever a dependency changes, or remove it if the result is `None`.
+/// - `on:event={handler}` on an element will call `handler` whenever `event` is received, where
+///   `event` is the name of a kind in `kinesis::event::on` (so an unknown event will fail to
+///   compile). Options and modifiers from `EventOptions` may follow the event, such as
+///   `on:submit|prevent_default={handler}` or `on:click|once|self_only={handler}`.
+/// - `bind:value={#(id, get_value, set_value)}` on a form element will keep its `value` in sync
+///   with the field `id`, setting it from `get_value` and writing changes from the user back with
+///   `set_value`. `bind:checked` does the same for the `checked` state of a checkbox.
+/// - `{#name(dependencies, ...)}` is expanded to `.with_name(dependencies, location, ...)`, which
+///   allows for any dynamic helper on the builder to be used (eg `{#iter(&[0], || ...)}`).
+/// - `{#slot()}` places the content passed into the component's default slot, and
+///   `{#slot(name)}` places the content for a named slot.
+#[proc_macro]
+pub fn html(input: TokenStream) -> TokenStream {
+    let markup = parse_macro_input!(input as html::Markup);
+
+    html::expand(markup).into()
+}
+
+/// Implement `Component` for the type of an impl block, using each method marked with `#[event]`
+/// as an event handler.
+///
+/// Each handler is assigned an event id in the order it is declared, which is exposed as a
+/// constant named after the method (eg `fn on_increment` becomes `Self::ON_INCREMENT`). These
+/// constants should be used when registering events, so that a mismatched id will fail to
+/// compile.
+///
+/// Handlers must take `&mut self`, and may optionally take the event. The event may be any type
+/// that the kind of event it is bound to can be used as, such as a `MouseEvent` for `on:click`, or
+/// an `Event` for any kind. A handler taking the event fails with `Error::MissingEvent` when it
+/// receives an event without a DOM event (such as from the `MemoryBackend`). They may also take a
+/// `Spawner<Self>` as their last argument, which can spawn futures that update the component once
+/// they complete. They can either return the changed fields (`Option<Vec<Field>>`), or nothing if
+/// no fields were changed.
+///
+/// A single method may be marked with `#[props]` to receive props from the parent component,
+/// which sets the `Props` of the component to the type that it takes by reference. It may
+/// optionally take the changed fields of the props (`&[usize]`), and returns the changed fields
+/// in the same way as an event handler. If there is no such method, the component's props are
+/// `()`.
+///
+/// The fields of the component are identified by the type provided with
+/// `#[component(fields = CounterField)]`, which should implement `Field` (typically with
+/// `#[derive(Field)]`). If no type is provided, fields are identified by a `usize`.
+///
+/// A stylesheet may be provided with `#[component(styles = "...")]` (or any expression that
+/// evaluates to a `&'static str`, such as `include_str!("button.css")`), which will be scoped so
+/// that it only applies to elements created by the component.
+#[proc_macro_attribute]
+pub fn component(attribute: TokenStream, input: TokenStream) -> TokenStream {
+    let mut args = component::ComponentArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attribute with parser);
+
+    let item = parse_macro_input!(input as ItemImpl);
+
+    component::expand(item, args)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Implement `Field` for an enum without any fields, allowing it to identify the fields of a
+/// component. Each variant is identified by its index, in the order that it is declared.
+#[proc_macro_derive(Field)]
+pub fn field(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    field::expand(item)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Implement `Props` for a struct, allowing it to be passed into a nested component. Each field
+/// is identified by its index, in the order that it is declared, and is compared with
+/// [`PartialEq`] to determine whether it has changed.
+#[proc_macro_derive(Props)]
+pub fn props(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    props::expand(item)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
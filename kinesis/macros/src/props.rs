@@ -0,0 +1,47 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Error, Index, Member, Result};
+
+/// Generate an implementation of `Props` for a struct, comparing each field with the previous
+/// props. Fields are identified by their index, in the order that they are declared.
+pub fn expand(item: DeriveInput) -> Result<TokenStream> {
+    let Data::Struct(data) = &item.data else {
+        return Err(Error::new(
+            item.ident.span(),
+            "`Props` can only be derived for structs",
+        ));
+    };
+
+    let comparisons = data.fields.iter().enumerate().map(|(index, field)| {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index {
+                index: index as u32,
+                span: field.span(),
+            }),
+        };
+
+        quote! {
+            if previous.is_none_or(|previous| self.#member != previous.#member) {
+                changed.push(#index);
+            }
+        }
+    });
+
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::kinesis::component::Props for #ident #ty_generics #where_clause {
+            #[allow(unused_variables, unused_mut)]
+            fn changed(
+                &self,
+                previous: ::std::option::Option<&Self>,
+            ) -> ::std::vec::Vec<usize> {
+                let mut changed = ::std::vec::Vec::new();
+                #(#comparisons)*
+                changed
+            }
+        }
+    })
+}
@@ -0,0 +1,7 @@
+use kinesis_macros::html;
+
+fn main() {
+    html! {
+        <p>"some text"</b>
+    };
+}
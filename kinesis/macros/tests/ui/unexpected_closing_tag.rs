@@ -0,0 +1,8 @@
+use kinesis_macros::html;
+
+fn main() {
+    html! {
+        <p>"some text"</p>
+        </div>
+    };
+}
@@ -0,0 +1,737 @@
+use super::{Backend, NodeKind, PropertyValue};
+use crate::event::{EventOptions, EventPayload};
+use crate::Error;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    iter,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
+
+/// A listener bound to a [`MemoryNode`].
+pub type MemoryListener = Rc<dyn Fn(MemoryEvent) -> Result<(), Error>>;
+
+/// Work scheduled with a [`MemoryBackend`].
+type ScheduledFn = Box<dyn FnOnce() -> Result<(), Error>>;
+
+/// A future spawned with a [`MemoryBackend`].
+type SpawnedFuture = Pin<Box<dyn Future<Output = Result<(), Error>>>>;
+
+/// A [`Backend`] that renders to a tree of [`MemoryNode`]s, which doesn't require a browser. This
+/// allows for the entire rendering pipeline to be run natively.
+///
+/// As there is no event loop, scheduled work is queued until [`MemoryBackend::run_scheduled()`]
+/// is called. Clones of the backend share the same queue.
+///
+/// Each backend has its own document (an `html` element containing a `head` and a `body`), which
+/// [`Backend::query_selector()`] will search. Selectors may only be a single element kind (eg
+/// `div`), id (eg `#modal`), or class (eg `.toast`). Injected stylesheets aren't added to the
+/// tree, and are instead collected so that they can be retrieved with [`MemoryBackend::styles()`].
+/// As in a browser, setting the `value` of a `<select>` which doesn't match any of its options
+/// will clear it.
+#[derive(Clone)]
+pub struct MemoryBackend {
+    scheduled: Rc<RefCell<VecDeque<ScheduledFn>>>,
+
+    /// Spawned futures which haven't yet completed.
+    spawned: Rc<RefCell<Vec<SpawnedFuture>>>,
+
+    /// The root of the document, which selectors are matched against.
+    document: MemoryNode,
+
+    /// The `body` element within the document.
+    body: MemoryNode,
+
+    /// Whether a selector which doesn't match anything should instead find a detached element.
+    detached_fallback: bool,
+
+    /// Injected stylesheets, along with their scope, in the order they were injected.
+    styles: Rc<RefCell<Vec<(String, String)>>>,
+}
+
+impl MemoryBackend {
+    /// Create a new backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find a new detached element with [`Backend::query_selector()`] for any selector which
+    /// doesn't match, rather than nothing. Anything mounted within it (such as the content of a
+    /// [`crate::dynamic::Portal`]) is kept out of the document, which is used when rendering to a
+    /// string where the target may only exist on the page that the markup is placed within.
+    pub fn with_detached_fallback(mut self) -> Self {
+        self.detached_fallback = true;
+        self
+    }
+
+    /// Get the `body` element of the document.
+    pub fn body(&self) -> MemoryNode {
+        self.body.clone()
+    }
+
+    /// Get each of the injected stylesheets, along with their scope.
+    pub fn styles(&self) -> Vec<(String, String)> {
+        self.styles.borrow().clone()
+    }
+
+    /// Run all scheduled work, including anything that is scheduled whilst running. Spawned
+    /// futures are polled once the queue is empty, until none of them are able to make progress.
+    /// Stops at the first error.
+    pub fn run_scheduled(&self) -> Result<(), Error> {
+        loop {
+            // Only borrow the queue to take the next item, as the item may schedule more work
+            let callback = self.scheduled.borrow_mut().pop_front();
+
+            match callback {
+                Some(callback) => callback()?,
+                None if self.poll_spawned()? => (),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Get the number of spawned futures which haven't yet completed.
+    pub fn spawned(&self) -> usize {
+        self.spawned.borrow().len()
+    }
+
+    /// Helper function to poll each spawned future once, returning whether any of them completed
+    /// or spawned another future. There is no event loop to wake them, so futures are only polled
+    /// when work is run.
+    fn poll_spawned(&self) -> Result<bool, Error> {
+        // Take the futures, as they may spawn more futures whilst being polled
+        let spawned = self.spawned.take();
+        let count = spawned.len();
+        let mut context = Context::from_waker(Waker::noop());
+
+        let mut pending = Vec::with_capacity(count);
+        let mut result = Ok(());
+        for mut future in spawned {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(Ok(())) => (),
+                Poll::Ready(Err(error)) => result = result.and(Err(error)),
+                Poll::Pending => pending.push(future),
+            }
+        }
+
+        // Any newly spawned futures must also be polled
+        let progressed = pending.len() != count || !self.spawned.borrow().is_empty();
+        self.spawned.borrow_mut().splice(0..0, pending);
+
+        result.map(|_| progressed)
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        let document = MemoryNode::new(MemoryNodeKind::Element("html".to_string()));
+        let head = MemoryNode::new(MemoryNodeKind::Element("head".to_string()));
+        let body = MemoryNode::new(MemoryNodeKind::Element("body".to_string()));
+
+        for child in [&head, &body] {
+            document.0.borrow_mut().children.push(child.clone());
+            child.0.borrow_mut().parent = Rc::downgrade(&document.0);
+        }
+
+        Self {
+            scheduled: Rc::default(),
+            spawned: Rc::default(),
+            document,
+            body,
+            detached_fallback: false,
+            styles: Rc::default(),
+        }
+    }
+}
+
+/// The content of a [`MemoryNode`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemoryNodeKind {
+    /// An element, containing the element kind (eg `p`, `div`).
+    Element(String),
+
+    /// A text node, containing its content.
+    Text(String),
+}
+
+/// An event which can be dispatched to a [`MemoryNode`]. Cloning the event will refer to the same
+/// event, so whether it was prevented or stopped can be checked once it has been dispatched.
+#[derive(Clone)]
+pub struct MemoryEvent(Rc<MemoryEventData>);
+
+struct MemoryEventData {
+    event_type: String,
+
+    /// Whether the event propagates to the ancestors of its target.
+    bubbles: bool,
+
+    /// The node that the event was dispatched to.
+    target: RefCell<Option<MemoryNode>>,
+
+    /// The node whose listeners are currently being called.
+    current_target: RefCell<Option<MemoryNode>>,
+
+    default_prevented: Cell<bool>,
+    propagation_stopped: Cell<bool>,
+}
+
+impl MemoryEvent {
+    /// Create a new event of the provided type (eg `click`), which bubbles.
+    pub fn new(event_type: &str) -> Self {
+        Self::create(event_type, true)
+    }
+
+    /// Create a new event of the provided type which doesn't bubble (eg `focus`), so it is only
+    /// received by listeners bound to its target.
+    pub fn non_bubbling(event_type: &str) -> Self {
+        Self::create(event_type, false)
+    }
+
+    /// Helper function to create an event.
+    fn create(event_type: &str, bubbles: bool) -> Self {
+        Self(Rc::new(MemoryEventData {
+            event_type: event_type.to_string(),
+            bubbles,
+            target: RefCell::new(None),
+            current_target: RefCell::new(None),
+            default_prevented: Cell::new(false),
+            propagation_stopped: Cell::new(false),
+        }))
+    }
+
+    /// Get the type of the event.
+    pub fn event_type(&self) -> &str {
+        &self.0.event_type
+    }
+
+    /// Whether the default action of the event has been prevented.
+    pub fn default_prevented(&self) -> bool {
+        self.0.default_prevented.get()
+    }
+
+    /// Whether the event has been stopped from propagating.
+    pub fn propagation_stopped(&self) -> bool {
+        self.0.propagation_stopped.get()
+    }
+}
+
+/// A listener bound to a [`MemoryNode`], along with the event type it is bound to.
+struct MemoryListenerBinding {
+    event_type: String,
+    listener: MemoryListener,
+
+    /// Whether the listener should be removed after it is first called.
+    once: bool,
+}
+
+struct MemoryNodeData {
+    kind: MemoryNodeKind,
+
+    /// Attributes of the node, in the order they were first set.
+    attributes: Vec<(String, String)>,
+
+    /// Properties of the node, in the order they were first set.
+    properties: Vec<(String, PropertyValue)>,
+
+    /// Listeners bound to the node.
+    listeners: Vec<MemoryListenerBinding>,
+
+    parent: Weak<RefCell<MemoryNodeData>>,
+    children: Vec<MemoryNode>,
+}
+
+/// A handle to a node within an in-memory tree. Cloning the handle will refer to the same node.
+#[derive(Clone)]
+pub struct MemoryNode(Rc<RefCell<MemoryNodeData>>);
+
+impl MemoryNode {
+    fn new(kind: MemoryNodeKind) -> Self {
+        Self(Rc::new(RefCell::new(MemoryNodeData {
+            kind,
+            attributes: Vec::new(),
+            properties: Vec::new(),
+            listeners: Vec::new(),
+            parent: Weak::new(),
+            children: Vec::new(),
+        })))
+    }
+
+    /// Get the kind of the node.
+    pub fn kind(&self) -> MemoryNodeKind {
+        self.0.borrow().kind.clone()
+    }
+
+    /// Get the value of an attribute on the node.
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        self.0
+            .borrow()
+            .attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Get all of the attributes on the node, in the order they were first set.
+    pub fn attributes(&self) -> Vec<(String, String)> {
+        self.0.borrow().attributes.clone()
+    }
+
+    /// Get all of the properties on the node, in the order they were first set.
+    pub fn properties(&self) -> Vec<(String, PropertyValue)> {
+        self.0.borrow().properties.clone()
+    }
+
+    /// Get the event type of each listener bound to the node, in the order they were bound.
+    pub fn listeners(&self) -> Vec<String> {
+        self.0
+            .borrow()
+            .listeners
+            .iter()
+            .map(|binding| binding.event_type.clone())
+            .collect()
+    }
+
+    /// Get the parent of the node, if it has one.
+    pub fn parent(&self) -> Option<MemoryNode> {
+        self.0.borrow().parent.upgrade().map(MemoryNode)
+    }
+
+    /// Get the children of the node.
+    pub fn children(&self) -> Vec<MemoryNode> {
+        self.0.borrow().children.clone()
+    }
+
+    /// Get the combined content of all text nodes within this node, in order.
+    pub fn text_content(&self) -> String {
+        match &self.0.borrow().kind {
+            MemoryNodeKind::Text(content) => content.clone(),
+            MemoryNodeKind::Element(_) => self
+                .0
+                .borrow()
+                .children
+                .iter()
+                .map(MemoryNode::text_content)
+                .collect(),
+        }
+    }
+
+    /// Dispatch an event to this node, calling each of the listeners bound to it for the event's
+    /// type, followed by the listeners bound to each of its ancestors (if the event bubbles) until
+    /// propagation is stopped. There is no capture phase. Stops at the first listener to return an
+    /// error. Listeners bound with `once` are removed before they are called.
+    pub fn dispatch(&self, event: &MemoryEvent) -> Result<(), Error> {
+        *event.0.target.borrow_mut() = Some(self.clone());
+
+        for node in iter::successors(Some(self.clone()), MemoryNode::parent) {
+            *event.0.current_target.borrow_mut() = Some(node.clone());
+            node.call_listeners(event)?;
+
+            if event.propagation_stopped() || !event.0.bubbles {
+                break;
+            }
+        }
+
+        *event.0.current_target.borrow_mut() = None;
+
+        Ok(())
+    }
+
+    /// Helper function to call each of the listeners bound to this node for the event's type.
+    fn call_listeners(&self, event: &MemoryEvent) -> Result<(), Error> {
+        let event_type = event.event_type();
+
+        // Collect the listeners first, so that the node can be mutated by the listeners
+        let listeners = {
+            let mut data = self.0.borrow_mut();
+
+            let listeners = data
+                .listeners
+                .iter()
+                .filter(|binding| binding.event_type == event_type)
+                .map(|binding| Rc::clone(&binding.listener))
+                .collect::<Vec<_>>();
+
+            data.listeners
+                .retain(|binding| !(binding.once && binding.event_type == event_type));
+
+            listeners
+        };
+
+        listeners
+            .into_iter()
+            .try_for_each(|listener| listener(event.clone()))
+    }
+
+    /// Find the first element matching `selector`, including this node, in document order.
+    fn find(&self, selector: &str) -> Option<MemoryNode> {
+        if self.matches(selector) {
+            return Some(self.clone());
+        }
+
+        self.children()
+            .iter()
+            .find_map(|child| child.find(selector))
+    }
+
+    /// Check whether the node is an element matching a single kind, id or class selector.
+    fn matches(&self, selector: &str) -> bool {
+        let MemoryNodeKind::Element(kind) = self.kind() else {
+            return false;
+        };
+
+        if let Some(id) = selector.strip_prefix('#') {
+            self.attribute("id").as_deref() == Some(id)
+        } else if let Some(class) = selector.strip_prefix('.') {
+            self.attribute("class")
+                .is_some_and(|classes| classes.split_whitespace().any(|name| name == class))
+        } else {
+            kind == selector
+        }
+    }
+
+    /// Check whether the node is an element of the provided kind.
+    pub(crate) fn is_element(&self, kind: &str) -> bool {
+        matches!(&self.0.borrow().kind, MemoryNodeKind::Element(element) if element == kind)
+    }
+
+    /// Get the value of an `<option>`, which is its text if it has no `value` attribute.
+    pub(crate) fn option_value(&self) -> String {
+        self.attribute("value")
+            .unwrap_or_else(|| self.text_content().trim().to_string())
+    }
+
+    /// Check whether any `<option>` within the node has the provided value.
+    fn has_option(&self, value: &str) -> bool {
+        self.children().iter().any(|child| {
+            if child.is_element("option") {
+                child.option_value() == value
+            } else {
+                child.has_option(value)
+            }
+        })
+    }
+
+    /// Get the sibling directly after the node, if it has one.
+    fn next_sibling(&self) -> Option<MemoryNode> {
+        let parent = self.parent()?;
+        let siblings = parent.0.borrow();
+        let position = siblings.children.iter().position(|child| child == self)?;
+
+        siblings.children.get(position + 1).cloned()
+    }
+
+    /// Remove the node from its parent, if it has one.
+    fn detach(&self) {
+        let parent = self.0.borrow_mut().parent.upgrade();
+
+        if let Some(parent) = parent {
+            parent
+                .borrow_mut()
+                .children
+                .retain(|child| !Rc::ptr_eq(&child.0, &self.0));
+        }
+
+        self.0.borrow_mut().parent = Weak::new();
+    }
+}
+
+impl PartialEq for MemoryNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Backend for MemoryBackend {
+    type Node = MemoryNode;
+    type Listener = MemoryListener;
+    type Event = MemoryEvent;
+
+    fn create_element(&self, kind: &str) -> Result<Self::Node, Error> {
+        Ok(MemoryNode::new(MemoryNodeKind::Element(kind.to_string())))
+    }
+
+    fn create_text(&self, content: &str) -> Self::Node {
+        MemoryNode::new(MemoryNodeKind::Text(content.to_string()))
+    }
+
+    fn query_selector(&self, selector: &str) -> Option<Self::Node> {
+        self.document.find(selector).or_else(|| {
+            self.detached_fallback
+                .then(|| MemoryNode::new(MemoryNodeKind::Element("div".to_string())))
+        })
+    }
+
+    fn set_text(node: &Self::Node, content: &str) {
+        if let MemoryNodeKind::Text(text) = &mut node.0.borrow_mut().kind {
+            *text = content.to_string();
+        }
+    }
+
+    fn parent(node: &Self::Node) -> Option<Self::Node> {
+        node.parent()
+    }
+
+    fn first_child(node: &Self::Node) -> Option<Self::Node> {
+        node.0.borrow().children.first().cloned()
+    }
+
+    fn next_sibling(node: &Self::Node) -> Option<Self::Node> {
+        node.next_sibling()
+    }
+
+    fn node_kind(node: &Self::Node) -> NodeKind {
+        match node.kind() {
+            MemoryNodeKind::Element(kind) => NodeKind::Element(kind),
+            MemoryNodeKind::Text(content) => NodeKind::Text(content),
+        }
+    }
+
+    fn insert_before(
+        parent: &Self::Node,
+        node: &Self::Node,
+        anchor: Option<&Self::Node>,
+    ) -> Result<(), Error> {
+        // As in a browser, the anchor must be a child of the parent
+        if let Some(anchor) = anchor {
+            if anchor.parent().as_ref() != Some(parent) {
+                return Err(Error::InsertNode);
+            }
+
+            // Inserting a node before itself leaves it where it is
+            if anchor == node {
+                return Ok(());
+            }
+        }
+
+        // Moving a node will remove it from its existing position
+        node.detach();
+
+        let mut parent_data = parent.0.borrow_mut();
+        let index = anchor
+            .and_then(|anchor| {
+                parent_data
+                    .children
+                    .iter()
+                    .position(|child| child == anchor)
+            })
+            .unwrap_or(parent_data.children.len());
+        parent_data.children.insert(index, node.clone());
+
+        node.0.borrow_mut().parent = Rc::downgrade(&parent.0);
+
+        Ok(())
+    }
+
+    fn remove(node: &Self::Node) -> Result<(), Error> {
+        if node.parent().is_none() {
+            return Err(Error::MissingParent);
+        }
+
+        node.detach();
+
+        Ok(())
+    }
+
+    fn get_attribute(node: &Self::Node, name: &str) -> Option<String> {
+        node.attribute(name)
+    }
+
+    fn set_attribute(node: &Self::Node, name: &str, value: &str) -> Result<(), Error> {
+        let attributes = &mut node.0.borrow_mut().attributes;
+
+        match attributes
+            .iter_mut()
+            .find(|(attribute, _)| attribute == name)
+        {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => attributes.push((name.to_string(), value.to_string())),
+        }
+
+        Ok(())
+    }
+
+    fn remove_attribute(node: &Self::Node, name: &str) -> Result<(), Error> {
+        node.0
+            .borrow_mut()
+            .attributes
+            .retain(|(attribute, _)| attribute != name);
+
+        Ok(())
+    }
+
+    fn property(node: &Self::Node, name: &str) -> Option<PropertyValue> {
+        node.0
+            .borrow()
+            .properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn set_property(node: &Self::Node, name: &str, value: &PropertyValue) -> Result<(), Error> {
+        // As in a browser, a `<select>` only takes the value of one of its options
+        let value = match value {
+            PropertyValue::String(selected)
+                if name == "value" && node.is_element("select") && !node.has_option(selected) =>
+            {
+                &PropertyValue::String(String::new())
+            }
+            value => value,
+        };
+
+        let properties = &mut node.0.borrow_mut().properties;
+
+        match properties.iter_mut().find(|(property, _)| property == name) {
+            Some((_, existing)) => *existing = value.clone(),
+            None => properties.push((name.to_string(), value.clone())),
+        }
+
+        Ok(())
+    }
+
+    fn create_listener<F>(callback: F) -> Self::Listener
+    where
+        F: 'static + Fn(Self::Event) -> Result<(), Error>,
+    {
+        Rc::new(callback)
+    }
+
+    fn event_target(event: &Self::Event) -> Option<Self::Node> {
+        event.0.target.borrow().clone()
+    }
+
+    fn event_at_target(event: &Self::Event) -> bool {
+        *event.0.target.borrow() == *event.0.current_target.borrow()
+    }
+
+    fn prevent_default(event: &Self::Event) {
+        event.0.default_prevented.set(true);
+    }
+
+    fn stop_propagation(event: &Self::Event) {
+        event.0.propagation_stopped.set(true);
+    }
+
+    fn event_payload(event: &Self::Event) -> EventPayload {
+        EventPayload::new(event.event_type())
+    }
+
+    fn schedule<F>(&self, callback: F) -> Result<(), Error>
+    where
+        F: 'static + FnOnce() -> Result<(), Error>,
+    {
+        self.scheduled.borrow_mut().push_back(Box::new(callback));
+
+        Ok(())
+    }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: 'static + Future<Output = Result<(), Error>>,
+    {
+        self.spawned.borrow_mut().push(Box::pin(future));
+    }
+
+    fn inject_styles(&self, scope: &str, css: &str) -> Result<(), Error> {
+        let mut styles = self.styles.borrow_mut();
+
+        if !styles.iter().any(|(existing, _)| existing == scope) {
+            styles.push((scope.to_string(), css.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn add_listener(
+        node: &Self::Node,
+        event_type: &str,
+        listener: &Self::Listener,
+        options: &EventOptions,
+    ) -> Result<(), Error> {
+        node.0.borrow_mut().listeners.push(MemoryListenerBinding {
+            event_type: event_type.to_string(),
+            listener: Rc::clone(listener),
+            once: options.once,
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_selector_searches_document() {
+        let backend = MemoryBackend::new();
+        let modals = backend.create_element("div").unwrap();
+        MemoryBackend::set_attribute(&modals, "id", "modals").unwrap();
+        MemoryBackend::insert_before(&backend.body(), &modals, None).unwrap();
+
+        assert!(backend.query_selector("head").is_some());
+        assert!(backend.query_selector("body") == Some(backend.body()));
+        assert!(backend.query_selector("#modals") == Some(modals));
+        assert!(backend.query_selector("#missing").is_none());
+    }
+
+    #[test]
+    fn insert_before_requires_anchor_within_parent() {
+        let backend = MemoryBackend::new();
+        let parent = backend.create_element("div").unwrap();
+        let anchor = backend.create_element("p").unwrap();
+        let node = backend.create_text("text");
+
+        assert_eq!(
+            MemoryBackend::insert_before(&parent, &node, Some(&anchor)),
+            Err(Error::InsertNode)
+        );
+        assert!(node.parent().is_none());
+
+        MemoryBackend::insert_before(&parent, &anchor, None).unwrap();
+        MemoryBackend::insert_before(&parent, &node, Some(&anchor)).unwrap();
+
+        assert!(parent.children() == [node, anchor]);
+    }
+
+    #[test]
+    fn non_bubbling_event_does_not_propagate() {
+        let backend = MemoryBackend::new();
+        let parent = backend.create_element("div").unwrap();
+        let child = backend.create_element("input").unwrap();
+        MemoryBackend::insert_before(&parent, &child, None).unwrap();
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        for (node, name) in [(&parent, "parent"), (&child, "child")] {
+            let received = Rc::clone(&received);
+            let listener = MemoryBackend::create_listener(move |_| {
+                received.borrow_mut().push(name);
+                Ok(())
+            });
+
+            for event_type in ["focus", "click"] {
+                MemoryBackend::add_listener(node, event_type, &listener, &EventOptions::default())
+                    .unwrap();
+            }
+        }
+
+        child.dispatch(&MemoryEvent::non_bubbling("focus")).unwrap();
+        assert_eq!(*received.borrow(), ["child"]);
+
+        child.dispatch(&MemoryEvent::new("click")).unwrap();
+        assert_eq!(*received.borrow(), ["child", "child", "parent"]);
+    }
+
+    #[test]
+    fn detached_fallback_finds_detached_element() {
+        let backend = MemoryBackend::new().with_detached_fallback();
+
+        let target = backend.query_selector("#missing").unwrap();
+
+        assert!(target.parent().is_none());
+    }
+}
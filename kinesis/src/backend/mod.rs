@@ -0,0 +1,154 @@
+mod memory;
+mod web;
+
+pub use memory::*;
+pub use web::*;
+
+use crate::event::{EventOptions, EventPayload};
+use crate::Error;
+
+use std::future::Future;
+
+/// The kind of a node that already exists, which is used to check that it matches what is
+/// expected when hydrating.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    /// An element, containing the element kind (eg `p`, `div`).
+    Element(String),
+
+    /// A text node, containing its content.
+    Text(String),
+
+    /// A comment.
+    Comment,
+
+    /// Any other kind of node.
+    Other,
+}
+
+/// The value of a property on an element. Properties may differ from the attribute of the same
+/// name, such as the `value` property of an `<input>` reflecting what has been typed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    /// A string property (eg `value`).
+    String(String),
+
+    /// A boolean property (eg `checked`).
+    Bool(bool),
+}
+
+/// Abstraction over the DOM, responsible for creating and manipulating nodes. All rendering goes
+/// through a backend, allowing for the same components to be rendered to the browser (with
+/// [`WebBackend`]), or to an in-memory tree (with [`MemoryBackend`]).
+///
+/// Only creating nodes requires an instance of the backend (as it may require a reference to
+/// something such as [`web_sys::Document`]). Everything else operates on nodes that have already
+/// been created.
+pub trait Backend: Clone + 'static {
+    /// A handle to a node created by the backend. Cloning the handle must refer to the same node,
+    /// and handles are only equal if they refer to the same node.
+    type Node: Clone + PartialEq + 'static;
+
+    /// A listener that can be bound to a node, and re-used across any number of nodes.
+    type Listener: 'static;
+
+    /// An event received by a listener. Cloning the event must refer to the same event.
+    type Event: Clone + 'static;
+
+    /// Create a new element of the provided kind (eg `p`, `div`).
+    fn create_element(&self, kind: &str) -> Result<Self::Node, Error>;
+
+    /// Create a new text node with the provided content.
+    fn create_text(&self, content: &str) -> Self::Node;
+
+    /// Find the first element matching a CSS selector within the document, if there is one.
+    fn query_selector(&self, selector: &str) -> Option<Self::Node>;
+
+    /// Replace the content of a text node.
+    fn set_text(node: &Self::Node, content: &str);
+
+    /// Get the parent of a node, if it has one.
+    fn parent(node: &Self::Node) -> Option<Self::Node>;
+
+    /// Get the first child of a node, if it has any.
+    fn first_child(node: &Self::Node) -> Option<Self::Node>;
+
+    /// Get the sibling directly after a node, if it has one.
+    fn next_sibling(node: &Self::Node) -> Option<Self::Node>;
+
+    /// Get the kind of a node.
+    fn node_kind(node: &Self::Node) -> NodeKind;
+
+    /// Insert `node` within `parent`, before `anchor`, which must be a child of `parent`. If there
+    /// is no anchor, the node will be appended to the parent. If `node` is already mounted, it will
+    /// be moved.
+    fn insert_before(
+        parent: &Self::Node,
+        node: &Self::Node,
+        anchor: Option<&Self::Node>,
+    ) -> Result<(), Error>;
+
+    /// Remove a node from its parent.
+    fn remove(node: &Self::Node) -> Result<(), Error>;
+
+    /// Get the value of an attribute on an element, if it is set.
+    fn get_attribute(node: &Self::Node, name: &str) -> Option<String>;
+
+    /// Set an attribute on an element.
+    fn set_attribute(node: &Self::Node, name: &str, value: &str) -> Result<(), Error>;
+
+    /// Remove an attribute from an element.
+    fn remove_attribute(node: &Self::Node, name: &str) -> Result<(), Error>;
+
+    /// Get the value of a property on an element, if it has been set.
+    fn property(node: &Self::Node, name: &str) -> Option<PropertyValue>;
+
+    /// Set the value of a property on an element.
+    fn set_property(node: &Self::Node, name: &str, value: &PropertyValue) -> Result<(), Error>;
+
+    /// Create a listener which will call `callback` with every event that it receives. Any error
+    /// returned from the callback should be reported by the backend.
+    fn create_listener<F>(callback: F) -> Self::Listener
+    where
+        F: 'static + Fn(Self::Event) -> Result<(), Error>;
+
+    /// Get the node that an event was dispatched to, if it is a node.
+    fn event_target(event: &Self::Event) -> Option<Self::Node>;
+
+    /// Whether the listener receiving an event is bound to the node it was dispatched to.
+    fn event_at_target(event: &Self::Event) -> bool;
+
+    /// Prevent the default action of an event.
+    fn prevent_default(event: &Self::Event);
+
+    /// Stop an event from propagating any further.
+    fn stop_propagation(event: &Self::Event);
+
+    /// Create the payload passed to a component for an event.
+    fn event_payload(event: &Self::Event) -> EventPayload;
+
+    /// Schedule `callback` to be run once the current task has completed, allowing for work to be
+    /// batched. Any error returned from the callback should be reported by the backend.
+    fn schedule<F>(&self, callback: F) -> Result<(), Error>
+    where
+        F: 'static + FnOnce() -> Result<(), Error>;
+
+    /// Spawn `future` to run to completion in the background. Any error returned from the future
+    /// should be reported by the backend.
+    fn spawn<F>(&self, future: F)
+    where
+        F: 'static + Future<Output = Result<(), Error>>;
+
+    /// Add a stylesheet to the document, identified by `scope`. A stylesheet with the same scope
+    /// must only be added once.
+    fn inject_styles(&self, scope: &str, css: &str) -> Result<(), Error>;
+
+    /// Bind a listener to a node for the provided event type (eg `click`). Only the `capture`,
+    /// `once` and `passive` options apply to binding, as the modifiers are applied by the listener.
+    fn add_listener(
+        node: &Self::Node,
+        event_type: &str,
+        listener: &Self::Listener,
+        options: &EventOptions,
+    ) -> Result<(), Error>;
+}
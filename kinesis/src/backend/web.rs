@@ -0,0 +1,254 @@
+use super::{Backend, NodeKind, PropertyValue};
+use crate::event::{EventOptions, EventPayload};
+use crate::style::STYLE_ATTRIBUTE;
+use crate::Error;
+
+use std::future::Future;
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::{console, AddEventListenerOptions, Document, Element, Event, Node as WsNode};
+
+/// When scheduled work (such as batched updates) will be run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpdateTiming {
+    /// Run in a microtask, as soon as the current task has completed.
+    #[default]
+    Microtask,
+
+    /// Run before the next repaint, using `requestAnimationFrame`.
+    AnimationFrame,
+}
+
+/// A [`Backend`] that renders to the browser's DOM, using [`web_sys`].
+#[derive(Clone)]
+pub struct WebBackend {
+    /// A reference to the [`Document`], used to create new nodes.
+    document: Document,
+
+    /// When scheduled work will be run.
+    timing: UpdateTiming,
+}
+
+impl WebBackend {
+    /// Create a new backend, which will create nodes with the provided [`Document`].
+    pub fn new(document: &Document) -> Self {
+        Self {
+            document: document.clone(),
+            timing: UpdateTiming::default(),
+        }
+    }
+
+    /// Set when scheduled work will be run.
+    pub fn with_timing(mut self, timing: UpdateTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl Backend for WebBackend {
+    type Node = WsNode;
+    type Listener = Function;
+    type Event = Event;
+
+    fn create_element(&self, kind: &str) -> Result<Self::Node, Error> {
+        self.document
+            .create_element(kind)
+            .map(Into::into)
+            .map_err(|_| Error::CreateElement(kind.to_string()))
+    }
+
+    fn create_text(&self, content: &str) -> Self::Node {
+        self.document.create_text_node(content).into()
+    }
+
+    fn query_selector(&self, selector: &str) -> Option<Self::Node> {
+        self.document
+            .query_selector(selector)
+            .ok()
+            .flatten()
+            .map(Into::into)
+    }
+
+    fn set_text(node: &Self::Node, content: &str) {
+        node.set_node_value(Some(content));
+    }
+
+    fn parent(node: &Self::Node) -> Option<Self::Node> {
+        node.parent_node()
+    }
+
+    fn first_child(node: &Self::Node) -> Option<Self::Node> {
+        node.first_child()
+    }
+
+    fn next_sibling(node: &Self::Node) -> Option<Self::Node> {
+        node.next_sibling()
+    }
+
+    fn node_kind(node: &Self::Node) -> NodeKind {
+        match node.node_type() {
+            WsNode::ELEMENT_NODE => NodeKind::Element(node.unchecked_ref::<Element>().local_name()),
+            WsNode::TEXT_NODE => NodeKind::Text(node.node_value().unwrap_or_default()),
+            WsNode::COMMENT_NODE => NodeKind::Comment,
+            _ => NodeKind::Other,
+        }
+    }
+
+    fn insert_before(
+        parent: &Self::Node,
+        node: &Self::Node,
+        anchor: Option<&Self::Node>,
+    ) -> Result<(), Error> {
+        parent
+            .insert_before(node, anchor)
+            .map(|_| ())
+            .map_err(|_| Error::InsertNode)
+    }
+
+    fn remove(node: &Self::Node) -> Result<(), Error> {
+        node.parent_node()
+            .ok_or(Error::MissingParent)?
+            .remove_child(node)
+            .map(|_| ())
+            .map_err(|_| Error::RemoveNode)
+    }
+
+    fn get_attribute(node: &Self::Node, name: &str) -> Option<String> {
+        node.dyn_ref::<Element>()?.get_attribute(name)
+    }
+
+    fn set_attribute(node: &Self::Node, name: &str, value: &str) -> Result<(), Error> {
+        node.unchecked_ref::<Element>()
+            .set_attribute(name, value)
+            .map_err(|_| Error::SetAttribute(name.to_string()))
+    }
+
+    fn remove_attribute(node: &Self::Node, name: &str) -> Result<(), Error> {
+        node.unchecked_ref::<Element>()
+            .remove_attribute(name)
+            .map_err(|_| Error::SetAttribute(name.to_string()))
+    }
+
+    fn property(node: &Self::Node, name: &str) -> Option<PropertyValue> {
+        let value = Reflect::get(node, &name.into()).ok()?;
+
+        value
+            .as_bool()
+            .map(PropertyValue::Bool)
+            .or_else(|| value.as_string().map(PropertyValue::String))
+    }
+
+    fn set_property(node: &Self::Node, name: &str, value: &PropertyValue) -> Result<(), Error> {
+        let value = match value {
+            PropertyValue::String(value) => JsValue::from_str(value),
+            PropertyValue::Bool(value) => JsValue::from_bool(*value),
+        };
+
+        Reflect::set(node, &name.into(), &value)
+            .map(|_| ())
+            .map_err(|_| Error::SetProperty(name.to_string()))
+    }
+
+    fn create_listener<F>(callback: F) -> Self::Listener
+    where
+        F: 'static + Fn(Self::Event) -> Result<(), Error>,
+    {
+        // Errors are thrown as exceptions, so they are reported by the browser
+        Closure::<dyn Fn(Event) -> Result<(), JsValue>>::new(move |event| {
+            callback(event).map_err(JsValue::from)
+        })
+        .into_js_value()
+        .unchecked_into()
+    }
+
+    fn event_target(event: &Self::Event) -> Option<Self::Node> {
+        event.target()?.dyn_into().ok()
+    }
+
+    fn event_at_target(event: &Self::Event) -> bool {
+        event.target() == event.current_target()
+    }
+
+    fn prevent_default(event: &Self::Event) {
+        event.prevent_default();
+    }
+
+    fn stop_propagation(event: &Self::Event) {
+        event.stop_propagation();
+    }
+
+    fn event_payload(event: &Self::Event) -> EventPayload {
+        EventPayload::from_dom(event.clone())
+    }
+
+    fn schedule<F>(&self, callback: F) -> Result<(), Error>
+    where
+        F: 'static + FnOnce() -> Result<(), Error>,
+    {
+        let window = self.document.default_view().ok_or(Error::Schedule)?;
+
+        // Errors are thrown as exceptions, so they are reported by the browser
+        let callback = Closure::once_into_js(move || callback().map_err(JsValue::from));
+
+        match self.timing {
+            UpdateTiming::Microtask => {
+                window.queue_microtask(callback.unchecked_ref());
+                Ok(())
+            }
+            UpdateTiming::AnimationFrame => window
+                .request_animation_frame(callback.unchecked_ref())
+                .map(|_| ())
+                .map_err(|_| Error::Schedule),
+        }
+    }
+
+    fn spawn<F>(&self, future: F)
+    where
+        F: 'static + Future<Output = Result<(), Error>>,
+    {
+        wasm_bindgen_futures::spawn_local(async move {
+            // There is nowhere to throw an exception to, so log the error instead
+            if let Err(error) = future.await {
+                console::error_1(&error.into());
+            }
+        });
+    }
+
+    fn inject_styles(&self, scope: &str, css: &str) -> Result<(), Error> {
+        // Styles may have already been injected, or rendered on the server
+        if self
+            .query_selector(&format!("style[{STYLE_ATTRIBUTE}=\"{scope}\"]"))
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let head = self.query_selector("head").ok_or(Error::InjectStyles)?;
+
+        let style = self.create_element("style")?;
+        Self::set_attribute(&style, STYLE_ATTRIBUTE, scope)?;
+        style.set_text_content(Some(css));
+
+        Self::insert_before(&head, &style, None)
+    }
+
+    fn add_listener(
+        node: &Self::Node,
+        event_type: &str,
+        listener: &Self::Listener,
+        options: &EventOptions,
+    ) -> Result<(), Error> {
+        let listener_options = AddEventListenerOptions::new();
+        listener_options.set_capture(options.capture);
+        listener_options.set_once(options.once);
+        listener_options.set_passive(options.passive);
+
+        node.add_event_listener_with_callback_and_add_event_listener_options(
+            event_type,
+            listener,
+            &listener_options,
+        )
+        .map_err(|_| Error::BindListener(event_type.to_string()))
+    }
+}
@@ -0,0 +1,152 @@
+use std::{cell::RefCell, rc::Rc};
+
+/// A field whose value is computed from other fields of a component (eg `is_even` from `count`).
+/// Whenever one of its dependencies changes the value is recomputed, and the field is only
+/// considered to have changed if the new value differs from the previous one.
+pub struct Derived {
+    /// The id of the derived field.
+    id: usize,
+
+    /// The fields that the value is computed from.
+    dependencies: Vec<usize>,
+
+    /// Recompute the value, returning whether it differs from the previous value.
+    recompute: Box<dyn FnMut() -> bool>,
+}
+
+impl Derived {
+    /// Create a new derived field, computing its value from `component` with `compute`.
+    pub(crate) fn new<C, T, F>(
+        component: Rc<RefCell<C>>,
+        id: usize,
+        dependencies: &[usize],
+        compute: F,
+    ) -> Self
+    where
+        C: ?Sized + 'static,
+        T: PartialEq + 'static,
+        F: 'static + Fn(&C) -> T,
+    {
+        let mut previous = None;
+
+        Self {
+            id,
+            dependencies: dependencies.to_vec(),
+            recompute: Box::new(move || {
+                let value = compute(&component.borrow());
+                let changed = previous.as_ref() != Some(&value);
+                previous = Some(value);
+
+                changed
+            }),
+        }
+    }
+
+    /// Recompute the value, returning whether it changed. A value that has never been computed is
+    /// always considered to have changed.
+    pub(crate) fn recompute(&mut self) -> bool {
+        (self.recompute)()
+    }
+}
+
+/// Add the id of each derived field whose value changed as a result of `changed` to the list of
+/// changed fields. Fields are recomputed in the order they are declared, so a derived field may
+/// depend on any derived field declared before it.
+pub(crate) fn derive_changed(derived: &mut [Derived], changed: &mut Vec<usize>) {
+    for derived in derived {
+        let depends_on_changed = derived
+            .dependencies
+            .iter()
+            .any(|dependency| changed.contains(dependency));
+
+        // The value can only change along with a dependency, so the previous value is still
+        // current if none of them changed
+        if depends_on_changed && derived.recompute() && !changed.contains(&derived.id) {
+            changed.push(derived.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    const COUNT: usize = 0;
+    const HALF: usize = 1;
+    const HALF_IS_EVEN: usize = 2;
+
+    /// Helper function to create a derived field of `count`, which counts how many times it has
+    /// been computed.
+    fn derived<T: PartialEq + 'static>(
+        count: &Rc<RefCell<usize>>,
+        id: usize,
+        dependencies: &[usize],
+        compute: fn(usize) -> T,
+    ) -> (Derived, Rc<Cell<usize>>) {
+        let computed = Rc::new(Cell::new(0));
+
+        let derived = Derived::new(Rc::clone(count), id, dependencies, {
+            let computed = Rc::clone(&computed);
+            move |count: &usize| {
+                computed.set(computed.get() + 1);
+                compute(*count)
+            }
+        });
+
+        (derived, computed)
+    }
+
+    /// Helper function to get the fields which changed as a result of `changed`.
+    fn changed(derived: &mut [Derived], changed: &[usize]) -> Vec<usize> {
+        let mut changed = changed.to_vec();
+        derive_changed(derived, &mut changed);
+
+        changed
+    }
+
+    #[test]
+    fn only_changed_values_are_propagated() {
+        let count = Rc::new(RefCell::new(0));
+        let (is_even, computed) = derived(&count, HALF, &[COUNT], |count| count % 2 == 0);
+        let mut derived = [is_even];
+
+        // The first value is always considered changed
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT, HALF]);
+
+        *count.borrow_mut() = 2;
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT]);
+
+        *count.borrow_mut() = 3;
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT, HALF]);
+
+        // Unrelated changes don't recompute the value
+        assert_eq!(changed(&mut derived, &[HALF_IS_EVEN]), [HALF_IS_EVEN]);
+        assert_eq!(computed.get(), 3);
+    }
+
+    #[test]
+    fn derived_fields_can_be_chained() {
+        let count = Rc::new(RefCell::new(0));
+        let (half, _) = derived(&count, HALF, &[COUNT], |count| count / 2);
+        let (half_is_even, computed) =
+            derived(&count, HALF_IS_EVEN, &[HALF], |count| (count / 2) % 2 == 0);
+        let mut derived = [half, half_is_even];
+
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT, HALF, HALF_IS_EVEN]);
+
+        // Half is unchanged, so nothing depending on it is recomputed
+        *count.borrow_mut() = 1;
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT]);
+        assert_eq!(computed.get(), 1);
+
+        *count.borrow_mut() = 2;
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT, HALF, HALF_IS_EVEN]);
+
+        // Half changes, but whether it is even doesn't
+        *count.borrow_mut() = 6;
+        assert_eq!(changed(&mut derived, &[COUNT]), [COUNT, HALF]);
+        assert_eq!(computed.get(), 3);
+    }
+}
@@ -1,10 +1,91 @@
+pub(crate) mod derived;
 mod wrapper;
 
-use web_sys::Event;
+pub use derived::Derived;
+pub use kinesis_macros::{Field, Props};
 pub use wrapper::ComponentWrapper;
 
+use crate::event::{EventContext, EventPayload};
+use crate::Error;
+
 /// Trait that represents a component
 pub trait Component {
-    /// Handle an incomming event, allowing for mutation of the component's state.
-    fn handle_event(&mut self, event_id: usize, event: Event) -> Option<Vec<usize>>;
+    /// Data passed into the component by its parent component. Use `()` if the component doesn't
+    /// accept any props.
+    type Props: Props;
+
+    /// Identifies each field of the component, which dependencies and changes are described
+    /// with. Use `usize` to identify fields by number.
+    type Field: Field;
+
+    /// Handle an incomming event, allowing for mutation of the component's state. The event has
+    /// already been checked against the [`crate::event::EventKind`] that the handler was bound to.
+    /// `context` allows for work to continue after the handler returns, such as by spawning a
+    /// future with [`EventContext::spawner()`]. Fails if the handler can't be called with the
+    /// event, such as if it takes a DOM event and the event doesn't contain one.
+    fn handle_event(
+        &mut self,
+        event_id: usize,
+        event: EventPayload,
+        context: &EventContext,
+    ) -> Result<Option<Vec<Self::Field>>, Error>;
+
+    /// Handle new props from the parent component, allowing for mutation of the component's
+    /// state. `changed` contains the fields of the props which differ from the previous props (see
+    /// [`Props::changed()`]), and will never be empty.
+    fn handle_props(&mut self, props: &Self::Props, changed: &[usize]) -> Option<Vec<Self::Field>>;
+
+    /// The stylesheet for the component, if it has one. Selectors within it will only apply to
+    /// elements created by the component, and it is added to the document when the first instance
+    /// of the component is mounted.
+    fn styles(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// A field of a component, which can be depended upon and marked as changed. Typically an enum
+/// with a variant for each field, so that a dependency on a field that doesn't exist will fail to
+/// compile. This can be implemented with `#[derive(Field)]`.
+///
+/// Whilst rendering, fields are represented by their index, so each field must have a unique
+/// index.
+pub trait Field: Copy + 'static {
+    /// Get the index of the field.
+    fn index(self) -> usize;
+
+    /// Get the field with the provided index, if there is one.
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+impl Field for usize {
+    fn index(self) -> usize {
+        self
+    }
+
+    fn from_index(index: usize) -> Option<Self> {
+        Some(index)
+    }
+}
+
+/// Helper function to get the index of each field.
+pub(crate) fn indexes<F: Field>(fields: &[F]) -> Vec<usize> {
+    fields.iter().copied().map(F::index).collect()
+}
+
+/// Data that is passed from a parent component into a nested component. Each field of the props
+/// is identified by its index, in the order that the fields are declared, so that only the fields
+/// which changed need to be passed to the nested component.
+///
+/// This can be implemented with `#[derive(Props)]`, which requires every field to implement
+/// [`PartialEq`].
+pub trait Props: 'static {
+    /// Get the fields which differ from `previous`. If there are no previous props, every field
+    /// is considered to have changed.
+    fn changed(&self, previous: Option<&Self>) -> Vec<usize>;
+}
+
+impl Props for () {
+    fn changed(&self, _previous: Option<&Self>) -> Vec<usize> {
+        Vec::new()
+    }
 }
@@ -1,38 +1,109 @@
-use super::Component;
+use super::{indexes, Component, Derived, Field};
+use crate::backend::{Backend, WebBackend};
+use crate::dynamic::DEFAULT_SLOT;
 use crate::fragment::FragmentBuilder;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
 /// Helper type to easily pass a constructed component around.
-pub struct ComponentWrapper<C: ?Sized + Component> {
+pub struct ComponentWrapper<C: ?Sized + Component, B: Backend = WebBackend> {
     /// A shared reference to the component.
     pub component: Rc<RefCell<C>>,
 
     /// The builder to construct the nodes for the component.
-    pub fragment_builder: FragmentBuilder,
+    pub fragment_builder: FragmentBuilder<B>,
+
+    /// Builders for content to place in the component's slots, along with the name of the slot.
+    pub slots: Vec<(String, FragmentBuilder<B>)>,
+
+    /// Fields of the component which are computed from other fields.
+    pub derived: Vec<Derived>,
+
+    /// Whether events are delegated to a listener on the component's root, rather than a listener
+    /// on each element.
+    pub delegate_events: bool,
 }
 
-impl<C: ?Sized + Component> ComponentWrapper<C> {
+impl<C: ?Sized + Component, B: Backend> ComponentWrapper<C, B> {
     /// Construct a new component wrapper
-    pub fn new(component: Rc<RefCell<C>>, fragment_builder: FragmentBuilder) -> Self {
+    pub fn new(component: Rc<RefCell<C>>, fragment_builder: FragmentBuilder<B, C::Field>) -> Self {
         Self {
             component,
-            fragment_builder,
+            fragment_builder: fragment_builder.erase_fields(),
+            slots: Vec::new(),
+            derived: Vec::new(),
+            delegate_events: false,
         }
     }
 
+    /// Provide content for the slot with the provided name. The content is built with the parent
+    /// component, so it may depend on the parent's fields (`F`) and trigger the parent's events.
+    pub fn with_slot<F: Field>(
+        mut self,
+        name: impl AsRef<str>,
+        content: FragmentBuilder<B, F>,
+    ) -> Self {
+        self.slots
+            .push((name.as_ref().to_string(), content.erase_fields()));
+        self
+    }
+
+    /// Provide content for the default slot, in the same way as [`Self::with_slot()`].
+    pub fn with_default_slot<F: Field>(self, content: FragmentBuilder<B, F>) -> Self {
+        self.with_slot(DEFAULT_SLOT, content)
+    }
+
+    /// Declare a field `id` whose value is computed from the component with `compute`. Whenever
+    /// any of the `dependencies` change, the value is recomputed, and `id` is included in the
+    /// changed fields if the value differs from the previous value. Derived fields may depend on
+    /// other derived fields, as long as they are declared after them.
+    pub fn with_derived<T, F>(mut self, id: C::Field, dependencies: &[C::Field], compute: F) -> Self
+    where
+        C: 'static,
+        T: PartialEq + 'static,
+        F: 'static + Fn(&C) -> T,
+    {
+        self.derived.push(Derived::new(
+            self.clone_component(),
+            id.index(),
+            &indexes(dependencies),
+            compute,
+        ));
+        self
+    }
+
+    /// Delegate the component's events to a single listener for each event type on the parent
+    /// that it is mounted within, rather than binding a listener to every element. Elements are
+    /// instead tagged with the handlers for their events, which are found by walking up from the
+    /// target of each event. This makes building many elements (such as a long list) cheaper.
+    ///
+    /// Listeners for events which don't bubble (such as `focus`), or with the `capture`, `once` or
+    /// `passive` options, are still bound to their element. Events from elements outside of the
+    /// parent (such as within a portal) aren't received. Slot content is built by the parent
+    /// component, so is only delegated if the parent is.
+    pub fn with_delegated_events(mut self) -> Self {
+        self.delegate_events = true;
+        self
+    }
+
     /// Clone the reference to the component
     pub fn clone_component(&self) -> Rc<RefCell<C>> {
         Rc::clone(&self.component)
     }
 }
 
-impl<C: Component + 'static> ComponentWrapper<C> {
-    pub fn into_any(self) -> ComponentWrapper<dyn Component> {
-        ComponentWrapper::<dyn Component> {
-            component: self.component as Rc<RefCell<dyn Component>>,
+impl<C: Component + 'static, B: Backend> ComponentWrapper<C, B> {
+    pub fn into_any(
+        self,
+    ) -> ComponentWrapper<dyn Component<Props = C::Props, Field = C::Field>, B> {
+        ComponentWrapper::<dyn Component<Props = C::Props, Field = C::Field>, B> {
+            component: self.component
+                as Rc<RefCell<dyn Component<Props = C::Props, Field = C::Field>>>,
             fragment_builder: self.fragment_builder,
+            slots: self.slots,
+            derived: self.derived,
+            delegate_events: self.delegate_events,
         }
     }
 }
@@ -1,20 +1,36 @@
+pub(crate) mod origin;
 mod r#ref;
+mod spawner;
+pub(crate) mod task;
 
 pub use self::r#ref::ControllerRef;
+pub use self::spawner::Spawner;
+use self::task::Task;
+use crate::backend::{Backend, WebBackend};
+use crate::component::derived::{derive_changed, Derived};
 use crate::component::{Component, ComponentWrapper};
-use crate::dynamic::{Dynamic, UpdateFn};
+use crate::dynamic::{Dynamic, SlotContent, UpdateFn};
+use crate::event::EventContext;
 use crate::event_registry::EventRegistry;
-use crate::fragment::{Fragment, Location};
+use crate::fragment::{Cursor, Fragment, Location};
+use crate::style::Stylesheet;
+use crate::Error;
 
 use std::{cell::RefCell, rc::Rc};
-use web_sys::{console, Document};
+
+/// A changed field, along with the identifier of the controller it originated from (if any).
+type PendingChange = (usize, Option<usize>);
+
+/// A newly created controller, along with the content for each of its slots.
+type CreatedController<C, B> = (Rc<RefCell<Controller<C, B>>>, Vec<SlotContent<B>>);
 
 /// A component controller, responsible for controlling the top level [`Fragment`] for a component,
 /// in addition to the initial mount and update, and passing of updates from events into the
 /// component.
-pub struct Controller<C>
+pub struct Controller<C, B = WebBackend>
 where
     C: Component + ?Sized,
+    B: Backend,
 {
     /// The component to be rendered. This will be used as the context for the [`Fragment`].
     /// Wrapping it in [`Rc<RefCell<T>>`] allows for access to the component in callbacks and in
@@ -23,95 +39,883 @@ where
 
     pub bound_update: Option<Rc<Box<UpdateFn>>>,
 
-    /// The [`EventRegistry`] for this component. Responsible for creating [`js_sys::Function`]s
+    /// The [`EventRegistry`] for this component. Responsible for creating [`Backend::Listener`]s
     /// for a given `event_id`, and caching it so it can be re-used for future renders. Wrapped in
     /// an [`Rc<RefCell<T>>`] in order to share the same instance with children [`Fragment`]s.
-    event_registry: Rc<RefCell<EventRegistry>>,
+    event_registry: Rc<RefCell<EventRegistry<B>>>,
 
     /// The top level fragment that
-    fragment: RefCell<Fragment>,
+    fragment: RefCell<Fragment<B>>,
+
+    /// A reference to the [`Backend`], required to begin hydrating and to schedule updates.
+    backend: B,
+
+    /// Changed fields which are waiting for a scheduled update to be applied, along with the
+    /// nested controller that the change originated from (if any). If [`None`], no update is
+    /// currently scheduled.
+    pending: RefCell<Option<Vec<PendingChange>>>,
+
+    /// Unique identifier for this controller, used to track where changes originated from.
+    id: usize,
+
+    /// The scoped stylesheet of the component, which is injected when mounted.
+    styles: Option<Stylesheet>,
+
+    /// Fields of the component which are computed from other fields, and are recomputed whenever
+    /// their dependencies change.
+    derived: RefCell<Vec<Derived>>,
+
+    /// Futures spawned for the component which may still be running, so that they can be
+    /// cancelled when the component is detached.
+    tasks: RefCell<Vec<Task>>,
 }
 
-impl<C> Controller<C>
+impl<C, B> Controller<C, B>
 where
     C: Component + ?Sized + 'static,
+    B: Backend,
 {
-    /// Create a new controller, returning a shared reference to the controller.
+    /// Create a new controller, returning a shared reference to the controller. As there is no
+    /// parent component, any slot content will be built with this component.
     pub fn new(
-        document: &Document,
-        component: ComponentWrapper<C>,
+        backend: &B,
+        component: ComponentWrapper<C, B>,
+        bound_update: Option<Box<UpdateFn>>,
+    ) -> Result<Rc<RefCell<Self>>, Error> {
+        Self::create(backend, component, bound_update, None).map(|(controller, _)| controller)
+    }
+
+    /// Create a new controller nested within a parent component, returning a shared reference to
+    /// the controller along with the content for each of its slots. The slot content is built with
+    /// the parent's [`EventRegistry`], and must be updated by the parent.
+    pub(crate) fn new_nested(
+        backend: &B,
+        component: ComponentWrapper<C, B>,
         bound_update: Option<Box<UpdateFn>>,
-    ) -> Rc<RefCell<Self>> {
+        parent_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<CreatedController<C, B>, Error> {
+        Self::create(backend, component, bound_update, Some(parent_registry))
+    }
+
+    /// Helper function to create a new controller, building slot content with `slot_registry`
+    /// (or the controller's own [`EventRegistry`] if there is none).
+    fn create(
+        backend: &B,
+        component: ComponentWrapper<C, B>,
+        bound_update: Option<Box<UpdateFn>>,
+        slot_registry: Option<&Rc<RefCell<EventRegistry<B>>>>,
+    ) -> Result<CreatedController<C, B>, Error> {
         // Create a reference to this controller. Initially contains `None`, however once the
         // controller is constructed it will be swapped in.
         let controller_reference = ControllerRef::new();
 
         // Create the event registry.
-        let event_registry = EventRegistry::new({
-            // Clone references that are required for use in the event registry.
-            let controller_reference = controller_reference.clone();
-            let component = component.clone_component();
-
-            move |event_id, event| {
-                // Perform a callback on the component
-                let changed = { component.borrow_mut().handle_event(event_id, event) };
-
-                if let Some(changed) = changed {
-                    console::log_1(&"about to notify".into());
-                    controller_reference.notify_changed(&changed);
+        let event_registry = EventRegistry::new(
+            {
+                // Clone references that are required for use in the event registry.
+                let controller_reference = controller_reference.clone();
+                let component = component.clone_component();
+
+                // Handlers are given a spawner rather than the reference, so that a component
+                // doesn't need to keep a reference to its own controller.
+                let context = EventContext::new(controller_reference.spawner());
+
+                move |event_id, event| {
+                    // Perform a callback on the component
+                    let changed = {
+                        component
+                            .borrow_mut()
+                            .handle_event(event_id, event, &context)?
+                    };
+
+                    match changed {
+                        Some(changed) => controller_reference.notify_changed(&changed),
+                        None => Ok(()),
+                    }
                 }
-            }
-        });
+            },
+            {
+                let controller_reference = controller_reference.clone();
+
+                move |changed| controller_reference.notify_changed_indexes(changed)
+            },
+        );
+
+        let id = origin::next_id();
+
+        // Tag elements with their events, rather than binding a listener to each.
+        if component.delegate_events {
+            event_registry.borrow_mut().delegate(id);
+        }
+
+        // Scope every element that the component creates to its stylesheet.
+        let styles = component.component.borrow().styles().map(Stylesheet::new);
+        if let Some(styles) = &styles {
+            event_registry.borrow_mut().set_scope(&styles.scope);
+        }
+
+        // Build the content for each slot, so that it can be placed within the fragment.
+        let slots = component
+            .slots
+            .into_iter()
+            .map(|(name, builder)| {
+                let content = Rc::new(RefCell::new(
+                    builder.build(backend, slot_registry.unwrap_or(&event_registry))?,
+                ));
+                event_registry
+                    .borrow_mut()
+                    .set_slot(&name, Rc::clone(&content));
+
+                Ok(content)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
 
         // Create the fragment for the component, passing it a reference to the event registry.
-        let fragment = component.fragment_builder.build(document, &event_registry);
+        let fragment = component.fragment_builder.build(backend, &event_registry)?;
 
         // Create the controller within a shared reference.
         let controller = Rc::new(RefCell::new(Self {
             component: component.component,
             event_registry,
             fragment: RefCell::new(fragment),
+            backend: backend.clone(),
+            pending: RefCell::new(None),
+            id,
+            styles,
+            derived: RefCell::new(component.derived),
+            tasks: RefCell::new(Vec::new()),
             bound_update: bound_update.map(|bound_update| Rc::new(bound_update)),
         }));
 
         // Place the reference to the controller within the shared self-reference.
         controller_reference.replace_with(&controller);
 
-        controller
+        Ok((controller, slots))
     }
 
     /// Mount the component to the provided [`Location`].
-    pub fn mount(&self, location: &Location) {
+    pub fn mount(&self, location: &Location<B>) -> Result<(), Error> {
+        self.inject_styles()?;
+        self.recompute_derived();
+        self.set_root(location.get_parent())?;
+
         let mut fragment = self.fragment.borrow_mut();
 
         // Mount the fragment at the provided location
-        fragment.mount(location);
+        fragment.mount(location)?;
 
         // Perform an update to get ensure the state is correct
-        fragment.full_update();
+        fragment.full_update()
+    }
+
+    /// Hydrate the component from the children of the parent of the provided [`Location`] (such
+    /// as server rendered markup), adopting the existing nodes and binding event listeners to
+    /// them rather than creating new nodes. Will return an error describing the first node that
+    /// doesn't match what the component would have rendered, including any nodes left over.
+    ///
+    /// The component must be the only content of the parent, as every child is adopted starting
+    /// from the first. The anchor of the location is ignored.
+    pub fn hydrate(&self, location: &Location<B>) -> Result<(), Error> {
+        self.inject_styles()?;
+        self.recompute_derived();
+        self.set_root(location.get_parent())?;
+
+        let mut cursor = Cursor::new(&self.backend, location.get_parent());
+        self.fragment.borrow_mut().hydrate(&mut cursor)?;
+
+        // Anything left over wasn't rendered by the component
+        cursor.finish()
     }
 
-    pub fn update_fragment(&self, changed: &[usize]) {
-        console::log_1(&"borrowing fragment".into());
-        self.fragment.borrow_mut().update(changed);
-        console::log_1(&"freeing fragment".into());
+    /// Detach the component from the DOM, cancelling any futures spawned for it (see
+    /// [`ControllerRef::spawn()`]).
+    pub fn detach(&self) -> Result<(), Error> {
+        self.cancel_tasks();
+
+        self.fragment.borrow_mut().detach(true)
+    }
+
+    pub fn update_fragment(&self, changed: &[usize]) -> Result<(), Error> {
+        self.fragment.borrow_mut().update(changed)
+    }
+
+    /// Get the unique identifier of this controller.
+    pub(crate) fn id(&self) -> usize {
+        self.id
     }
 }
 
-impl<C> Dynamic for Controller<C>
+impl<C, B> Controller<C, B>
 where
     C: Component + ?Sized,
+    B: Backend,
 {
-    fn mount(&mut self, location: &Location) {
+    /// Helper function to add the component's stylesheet to the document, if it has one. The
+    /// [`Backend`] will ignore it if it has already been added by another instance.
+    fn inject_styles(&self) -> Result<(), Error> {
+        match &self.styles {
+            Some(styles) => self.backend.inject_styles(&styles.scope, &styles.css),
+            None => Ok(()),
+        }
+    }
+
+    /// Helper function to set the root that the component's events are delegated to, if they are
+    /// delegated.
+    fn set_root(&self, root: &B::Node) -> Result<(), Error> {
+        self.event_registry.borrow_mut().set_root(root)
+    }
+
+    /// Helper function to recompute every derived field, so that later changes are compared
+    /// against the values that were rendered.
+    fn recompute_derived(&self) {
+        self.derived.borrow_mut().iter_mut().for_each(|derived| {
+            derived.recompute();
+        });
+    }
+
+    /// Helper function to cancel every future spawned for the component which is still running.
+    fn cancel_tasks(&self) {
+        self.tasks.take().iter().for_each(Task::cancel);
+    }
+
+    /// Keep track of a spawned future, so that it can be cancelled when the component is
+    /// detached.
+    pub(crate) fn add_task(&self, task: Task) {
+        let mut tasks = self.tasks.borrow_mut();
+
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(task);
+    }
+
+    /// Add any derived fields which changed as a result of `changed` to the changed fields.
+    pub(crate) fn derive_changed(&self, changed: &mut Vec<usize>) {
+        derive_changed(&mut self.derived.borrow_mut(), changed);
+    }
+}
+
+impl<C, B> Dynamic<B> for Controller<C, B>
+where
+    C: Component + ?Sized,
+    B: Backend,
+{
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.inject_styles()?;
+        self.recompute_derived();
+        self.set_root(location.get_parent())?;
+
+        let mut fragment = self.fragment.borrow_mut();
+
         // Mount the fragment to the specified location
-        self.fragment.borrow_mut().mount(location);
+        fragment.mount(location)?;
+
+        // The parent will only pass on the fields that changed, so perform an update to ensure
+        // that the state is correct
+        fragment.full_update()
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.inject_styles()?;
+        self.recompute_derived();
+        self.set_root(cursor.get_parent())?;
+
+        self.fragment.borrow_mut().hydrate(cursor)
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.cancel_tasks();
+
+        self.fragment.borrow_mut().detach(top_level)
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.fragment.borrow_mut().relocate(location)
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        let mut changed = changed.to_vec();
+        self.derive_changed(&mut changed);
+
+        self.fragment.borrow_mut().update(&changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MemoryBackend, MemoryEvent, MemoryNode, MemoryNodeKind};
+    use crate::component::{Field, Props};
+    use crate::event::MouseEvent;
+    use crate::event_registry::DELEGATE_ATTRIBUTE;
+    use crate::fragment::HydrationError;
+    use crate::{component, html};
+
+    use std::{cell::Cell, future::poll_fn, task::Poll};
+
+    #[derive(Clone, Copy, Field)]
+    enum CounterField {
+        Count,
+        Label,
+    }
+
+    #[derive(Props)]
+    struct CounterProps {
+        count: usize,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        count: usize,
+        label: String,
+
+        /// The number of times that props have been applied.
+        props_applied: usize,
+
+        /// Whether futures spawned by `increment_later` can complete.
+        ready: Rc<Cell<bool>>,
+    }
+
+    #[component(fields = CounterField)]
+    impl Counter {
+        #[props]
+        fn set_props(&mut self, props: &CounterProps) -> Option<Vec<CounterField>> {
+            self.props_applied += 1;
+            self.count = props.count;
+            Some(vec![CounterField::Count])
+        }
+
+        #[event]
+        fn increment(&mut self) -> Option<Vec<CounterField>> {
+            self.count += 1;
+            Some(vec![CounterField::Count])
+        }
+
+        #[event]
+        fn increment_later(&mut self, spawner: Spawner<Self>) {
+            let ready = Rc::clone(&self.ready);
+
+            spawner
+                .spawn(async move {
+                    poll_fn(|_| {
+                        if ready.get() {
+                            Poll::Ready(())
+                        } else {
+                            Poll::Pending
+                        }
+                    })
+                    .await;
+
+                    |counter: &mut Self| {
+                        counter.count += 1;
+                        Some(vec![CounterField::Count])
+                    }
+                })
+                .unwrap();
+        }
+
+        #[event]
+        fn increment_by_button(&mut self, event: MouseEvent) -> Option<Vec<CounterField>> {
+            self.count += event.button() as usize;
+            Some(vec![CounterField::Count])
+        }
+    }
+
+    /// The number of times that the count and the label of a counter have been rendered.
+    type Renders = Rc<Cell<(usize, usize)>>;
+
+    /// Helper function to create a counter, along with the number of times that the count and the
+    /// label have been rendered.
+    fn counter() -> (ComponentWrapper<Counter, MemoryBackend>, Renders) {
+        let component = Rc::new(RefCell::new(Counter::default()));
+        let renders = Rc::new(Cell::new((0, 0)));
+
+        let fragment = html! {
+            <p>
+                {#dynamic_text(&[CounterField::Count], {
+                    let component = Rc::clone(&component);
+                    let renders = Rc::clone(&renders);
+                    move || {
+                        let (count, label) = renders.get();
+                        renders.set((count + 1, label));
+                        component.borrow().count.to_string()
+                    }
+                })}
+            </p>
+            <span>
+                {#dynamic_text(&[CounterField::Label], {
+                    let component = Rc::clone(&component);
+                    let renders = Rc::clone(&renders);
+                    move || {
+                        let (count, label) = renders.get();
+                        renders.set((count, label + 1));
+                        component.borrow().label.clone()
+                    }
+                })}
+            </span>
+            <form on:submit|prevent_default={Counter::INCREMENT}>
+                <button
+                    on:click={Counter::INCREMENT}
+                    on:focus={Counter::INCREMENT}
+                    on:dblclick={Counter::INCREMENT_LATER}
+                    on:auxclick={Counter::INCREMENT_BY_BUTTON}
+                >
+                    "increment"
+                </button>
+            </form>
+        };
+
+        (ComponentWrapper::new(component, fragment), renders)
+    }
+
+    /// Helper function to mount a component within the body of a new backend.
+    fn mount(
+        component: ComponentWrapper<Counter, MemoryBackend>,
+    ) -> (
+        MemoryBackend,
+        Rc<RefCell<Controller<Counter, MemoryBackend>>>,
+    ) {
+        let backend = MemoryBackend::new();
+        let controller = Controller::new(&backend, component, None).unwrap();
+        controller
+            .borrow()
+            .mount(&Location::parent(&backend.body()))
+            .unwrap();
+
+        (backend, controller)
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.fragment.borrow_mut().detach(top_level);
+    /// Helper function to get the first descendant of `node` which is an element of `kind`.
+    fn find(node: &MemoryNode, kind: &str) -> Option<MemoryNode> {
+        node.children()
+            .into_iter()
+            .find_map(|child| match child.kind() {
+                MemoryNodeKind::Element(element) if element == kind => Some(child),
+                MemoryNodeKind::Element(_) => find(&child, kind),
+                MemoryNodeKind::Text(_) => None,
+            })
     }
 
-    fn update(&mut self, changed: &[usize]) {
-        self.fragment.borrow_mut().update(changed);
+    #[test]
+    fn mount_renders_fragment() {
+        let (component, renders) = counter();
+        component.clone_component().borrow_mut().label = "label".to_string();
+        let (backend, _controller) = mount(component);
+
+        let kinds = backend
+            .body()
+            .children()
+            .iter()
+            .map(MemoryNode::kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            ["p", "span", "form"].map(|kind| MemoryNodeKind::Element(kind.to_string()))
+        );
+        assert_eq!(backend.body().text_content(), "0labelincrement");
+        assert!(renders.get().0 > 0 && renders.get().1 > 0);
+    }
+
+    #[test]
+    fn update_only_renders_changed_dependencies() {
+        let (component, renders) = counter();
+        let state = component.clone_component();
+        let (backend, controller) = mount(component);
+        let (count, label) = renders.get();
+
+        state.borrow_mut().count = 5;
+        controller
+            .borrow()
+            .update_fragment(&[CounterField::Count.index()])
+            .unwrap();
+
+        assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "5");
+        assert_eq!(renders.get(), (count + 1, label));
+
+        state.borrow_mut().label = "changed".to_string();
+        controller
+            .borrow()
+            .update_fragment(&[CounterField::Label.index()])
+            .unwrap();
+
+        assert_eq!(
+            find(&backend.body(), "span").unwrap().text_content(),
+            "changed"
+        );
+        assert_eq!(renders.get(), (count + 1, label + 1));
+    }
+
+    #[test]
+    fn detach_removes_nodes() {
+        let (component, _) = counter();
+        let (backend, controller) = mount(component);
+
+        controller.borrow().detach().unwrap();
+
+        assert!(backend.body().children().is_empty());
+    }
+
+    #[test]
+    fn dispatched_events_update_component() {
+        for delegated in [false, true] {
+            let (mut component, _) = counter();
+            if delegated {
+                component = component.with_delegated_events();
+            }
+            let (backend, _controller) = mount(component);
+
+            find(&backend.body(), "button")
+                .unwrap()
+                .dispatch(&MemoryEvent::new("click"))
+                .unwrap();
+            backend.run_scheduled().unwrap();
+
+            assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "1");
+
+            // The event bubbles to the form, which also handles it
+            let event = MemoryEvent::new("submit");
+            find(&backend.body(), "button")
+                .unwrap()
+                .dispatch(&event)
+                .unwrap();
+            backend.run_scheduled().unwrap();
+
+            assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "2");
+            assert!(event.default_prevented());
+        }
+    }
+
+    #[test]
+    fn non_bubbling_events_are_not_delegated() {
+        for delegated in [false, true] {
+            let (mut component, _) = counter();
+            if delegated {
+                component = component.with_delegated_events();
+            }
+            let (backend, _controller) = mount(component);
+            let button = find(&backend.body(), "button").unwrap();
+
+            // The event never reaches the root, so must be received by the button itself
+            assert!(button.listeners().contains(&"focus".to_string()));
+            assert!(!backend.body().listeners().contains(&"focus".to_string()));
+
+            button
+                .dispatch(&MemoryEvent::non_bubbling("focus"))
+                .unwrap();
+            backend.run_scheduled().unwrap();
+
+            assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "1");
+        }
+    }
+
+    #[test]
+    fn delegated_events_stop_at_root() {
+        let (component, _) = counter();
+        let backend = MemoryBackend::new();
+        let root = backend.create_element("div").unwrap();
+        MemoryBackend::insert_before(&backend.body(), &root, None).unwrap();
+
+        let controller =
+            Controller::new(&backend, component.with_delegated_events(), None).unwrap();
+        controller.borrow().mount(&Location::parent(&root)).unwrap();
+
+        // Tag the ancestors of the root with the counter's click handler
+        let tagged = find(&root, "button")
+            .unwrap()
+            .attribute(DELEGATE_ATTRIBUTE)
+            .unwrap();
+        MemoryBackend::set_attribute(&root, DELEGATE_ATTRIBUTE, &tagged).unwrap();
+        MemoryBackend::set_attribute(&backend.body(), DELEGATE_ATTRIBUTE, &tagged).unwrap();
+
+        find(&root, "p")
+            .unwrap()
+            .dispatch(&MemoryEvent::new("click"))
+            .unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(find(&root, "p").unwrap().text_content(), "0");
+    }
+
+    #[test]
+    fn handlers_taking_dom_events_fail_without_one() {
+        let (component, _) = counter();
+        let (backend, _controller) = mount(component);
+
+        let result = find(&backend.body(), "button")
+            .unwrap()
+            .dispatch(&MemoryEvent::new("auxclick"));
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(result, Err(Error::MissingEvent("auxclick".to_string())));
+        assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "0");
+    }
+
+    #[test]
+    fn spawned_futures_update_component() {
+        let (component, _) = counter();
+        let state = component.clone_component();
+        let (backend, _controller) = mount(component);
+
+        find(&backend.body(), "button")
+            .unwrap()
+            .dispatch(&MemoryEvent::new("dblclick"))
+            .unwrap();
+        backend.run_scheduled().unwrap();
+        assert_eq!(backend.spawned(), 1);
+
+        state.borrow().ready.set(true);
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(backend.spawned(), 0);
+        assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "1");
+    }
+
+    #[test]
+    fn detach_cancels_spawned_futures() {
+        let (component, _) = counter();
+        let state = component.clone_component();
+        let (backend, controller) = mount(component);
+
+        find(&backend.body(), "button")
+            .unwrap()
+            .dispatch(&MemoryEvent::new("dblclick"))
+            .unwrap();
+        backend.run_scheduled().unwrap();
+        controller.borrow().detach().unwrap();
+
+        state.borrow().ready.set(true);
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(backend.spawned(), 0);
+        assert_eq!(state.borrow().count, 0);
+    }
+
+    #[test]
+    fn spawner_only_weakly_references_controller() {
+        let (component, _) = counter();
+        let backend = MemoryBackend::new();
+        let controller = Controller::new(&backend, component, None).unwrap();
+
+        let controller_ref = ControllerRef::new();
+        controller_ref.replace_with(&controller);
+        let spawner = controller_ref.spawner();
+        drop((controller, controller_ref));
+
+        assert_eq!(
+            spawner.spawn(async { |_: &mut Counter| None }),
+            Err(Error::UnboundController)
+        );
+    }
+
+    #[test]
+    fn hydrate_reports_extra_nodes() {
+        let hydrate = |extra_within: Option<&str>| {
+            let (component, _) = counter();
+            let (backend, _controller) = mount(component);
+
+            let extra = backend.create_element("i").unwrap();
+            let parent = match extra_within {
+                Some(kind) => find(&backend.body(), kind).unwrap(),
+                None => backend.body(),
+            };
+            MemoryBackend::insert_before(&parent, &extra, None).unwrap();
+
+            let (component, _) = counter();
+            Controller::new(&backend, component, None)
+                .unwrap()
+                .borrow()
+                .hydrate(&Location::parent(&backend.body()))
+        };
+
+        for (extra_within, within) in [(None, "element `body`"), (Some("p"), "element `p`")] {
+            assert!(matches!(
+                hydrate(extra_within),
+                Err(Error::Hydration(HydrationError { expected, found, within: found_within }))
+                    if expected == "nothing"
+                        && found == "element `i`"
+                        && found_within == within
+            ));
+        }
+    }
+
+    #[test]
+    fn hydrate_adopts_rendered_nodes() {
+        let (component, _) = counter();
+        let (backend, _controller) = mount(component);
+        let nodes = backend.body().children();
+
+        let (component, _) = counter();
+        let state = component.clone_component();
+        let controller = Controller::new(&backend, component, None).unwrap();
+        controller
+            .borrow()
+            .hydrate(&Location::parent(&backend.body()))
+            .unwrap();
+
+        assert!(backend.body().children() == nodes);
+
+        state.borrow_mut().count = 3;
+        controller
+            .borrow()
+            .update_fragment(&[CounterField::Count.index()])
+            .unwrap();
+
+        assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "3");
+    }
+
+    #[derive(Clone, Copy, Field)]
+    enum ParentField {
+        Count,
+        Broken,
+    }
+
+    #[derive(Default)]
+    struct Parent {
+        count: usize,
+
+        /// Renders a portal whose target doesn't exist, so that updating fails.
+        broken: bool,
+    }
+
+    #[component(fields = ParentField)]
+    impl Parent {}
+
+    /// A mounted parent, along with the counter nested within it.
+    struct MountedParent {
+        backend: MemoryBackend,
+        controller_ref: ControllerRef<Parent, MemoryBackend>,
+        parent: Rc<RefCell<Parent>>,
+        child: Rc<RefCell<Counter>>,
+    }
+
+    /// Helper function to mount a parent which passes its count to a nested counter, and copies the
+    /// count of the counter back whenever it changes. The parent breaks once the count reaches 2.
+    fn mount_parent() -> MountedParent {
+        let backend = MemoryBackend::new();
+        let controller_ref = ControllerRef::new();
+        let component = Rc::new(RefCell::new(Parent::default()));
+
+        let (child, _) = counter();
+        let child_component = child.clone_component();
+
+        let bound_update = {
+            let component = Rc::clone(&component);
+            let child = Rc::clone(&child_component);
+            let controller_ref = controller_ref.clone();
+
+            move |_: &[CounterField]| -> Option<Vec<ParentField>> {
+                {
+                    let mut component = component.borrow_mut();
+                    component.count = child.borrow().count;
+                    component.broken = component.count >= 2;
+                }
+
+                controller_ref
+                    .notify_changed(&[ParentField::Count, ParentField::Broken])
+                    .unwrap();
+
+                None
+            }
+        };
+
+        let fragment = html! {
+            {#conditional(
+                &[ParentField::Broken],
+                {
+                    let component = Rc::clone(&component);
+                    move || component.borrow().broken
+                },
+                || html! {
+                    {#portal(&[], "#missing", html! { <p>"broken"</p> })}
+                }
+            )}
+            {#component(&[ParentField::Count], child, {
+                let component = Rc::clone(&component);
+                move || CounterProps { count: component.borrow().count }
+            }, Some(bound_update))}
+        };
+
+        let controller = Controller::new(
+            &backend,
+            ComponentWrapper::new(Rc::clone(&component), fragment),
+            None,
+        )
+        .unwrap();
+        controller_ref.replace_with(&controller);
+        controller
+            .borrow()
+            .mount(&Location::parent(&backend.body()))
+            .unwrap();
+
+        MountedParent {
+            backend,
+            controller_ref,
+            parent: component,
+            child: child_component,
+        }
+    }
+
+    #[test]
+    fn nested_changes_are_not_passed_back() {
+        let MountedParent {
+            backend,
+            controller_ref,
+            parent,
+            child,
+        } = mount_parent();
+        assert_eq!(child.borrow().props_applied, 1);
+
+        find(&backend.body(), "button")
+            .unwrap()
+            .dispatch(&MemoryEvent::new("click"))
+            .unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(parent.borrow().count, 1);
+        assert_eq!(child.borrow().props_applied, 1);
+
+        // Changes made by the parent are still passed down
+        parent.borrow_mut().count = 5;
+        controller_ref
+            .notify_changed(&[ParentField::Count])
+            .unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(child.borrow().props_applied, 2);
+        assert_eq!(find(&backend.body(), "p").unwrap().text_content(), "5");
+    }
+
+    #[test]
+    fn origin_is_reset_after_flush_error() {
+        let MountedParent {
+            backend,
+            controller_ref,
+            parent,
+            child,
+        } = mount_parent();
+        let button = find(&backend.body(), "button").unwrap();
+
+        button.dispatch(&MemoryEvent::new("click")).unwrap();
+        backend.run_scheduled().unwrap();
+
+        // Reaching 2 breaks the parent whilst it is updating with the change from the counter
+        button.dispatch(&MemoryEvent::new("click")).unwrap();
+        assert!(matches!(
+            backend.run_scheduled(),
+            Err(Error::MissingPortalTarget(_))
+        ));
+        assert_eq!(origin::current(), None);
+
+        // The change would be ignored if it was still considered to be from the counter
+        *parent.borrow_mut() = Parent {
+            count: 10,
+            broken: false,
+        };
+        controller_ref
+            .get_ref()
+            .unwrap()
+            .borrow()
+            .update_fragment(&[ParentField::Count.index()])
+            .unwrap();
+
+        assert_eq!(child.borrow().count, 10);
     }
 }
@@ -0,0 +1,62 @@
+//! Tracking of which controller a change originated from. When a nested component writes into its
+//! parent (through its `bound_update`), the parent must not push the same change back down into
+//! the nested component, otherwise it will update twice.
+
+use std::{
+    cell::{Cell, RefCell},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Used to generate a unique identifier for each controller.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The controller whose changes are currently being propagated to its parent.
+    static CURRENT: Cell<Option<usize>> = const { Cell::new(None) };
+
+    /// Changed fields of the controller currently being updated, along with the controller that
+    /// each change originated from.
+    static UPDATING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Generate a new identifier for a controller.
+pub(crate) fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Get the controller whose changes are currently being propagated, if there is one.
+pub(crate) fn current() -> Option<usize> {
+    CURRENT.with(Cell::get)
+}
+
+/// Run `f`, marking any changes that occur within it as originating from the controller `id`.
+pub(crate) fn with_origin<T>(id: usize, f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT.with(|current| current.replace(Some(id)));
+    let result = f();
+    CURRENT.with(|current| current.set(previous));
+
+    result
+}
+
+/// Run `f` whilst updating with the provided changed fields and the controller they originated
+/// from, so that they can be retrieved with [`echoed()`].
+pub(crate) fn with_updating<T>(origins: Vec<(usize, usize)>, f: impl FnOnce() -> T) -> T {
+    let previous = UPDATING.with(|updating| updating.replace(origins));
+    let result = f();
+    UPDATING.with(|updating| *updating.borrow_mut() = previous);
+
+    result
+}
+
+/// Get the fields in the current update that originated from the controller `id`, which must not
+/// be passed back to it.
+pub(crate) fn echoed(id: usize) -> Vec<usize> {
+    UPDATING.with(|updating| {
+        updating
+            .borrow()
+            .iter()
+            .filter(|(_, origin)| *origin == id)
+            .map(|(field, _)| *field)
+            .collect()
+    })
+}
@@ -1,61 +1,191 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, future::Future, rc::Rc};
 
-use web_sys::console;
+use super::{origin, task::Task, Controller, Spawner};
+use crate::backend::{Backend, WebBackend};
+use crate::component::{indexes, Component};
+use crate::Error;
 
-use super::Controller;
-use crate::component::Component;
+/// A shared reference to a [`Controller`].
+type SharedController<C, B> = Rc<RefCell<Controller<C, B>>>;
 
-pub struct ControllerRef<C>(Rc<RefCell<Option<Rc<RefCell<Controller<C>>>>>>)
+pub struct ControllerRef<C, B = WebBackend>(Rc<RefCell<Option<SharedController<C, B>>>>)
 where
-    C: Component + ?Sized;
+    C: Component + ?Sized,
+    B: Backend;
 
-impl<C> ControllerRef<C>
+impl<C, B> ControllerRef<C, B>
 where
     C: Component + ?Sized + 'static,
+    B: Backend,
 {
     pub fn new() -> Self {
         Self(Rc::new(RefCell::new(None)))
     }
 
-    pub fn replace_with(&self, controller: &Rc<RefCell<Controller<C>>>) {
+    pub fn replace_with(&self, controller: &SharedController<C, B>) {
         *self.0.borrow_mut() = Some(Rc::clone(controller));
     }
 
-    pub fn get_ref(&self) -> Option<Rc<RefCell<Controller<C>>>> {
+    pub fn get_ref(&self) -> Option<SharedController<C, B>> {
         self.0.borrow().as_ref().map(Rc::clone)
     }
 
-    /// Handles updating all of the required parts of a component.
+    /// Notify the controller that the provided fields have changed. Changes are accumulated, and
+    /// a single update is scheduled with the [`Backend`] to apply all of them (see
+    /// [`Self::flush()`]), so that multiple changes in quick succession only result in a single
+    /// update.
+    ///
+    /// If called from the `bound_update` of a nested component, the change is recorded as
+    /// originating from that component, so that it won't be passed back down to it.
+    pub fn notify_changed(&self, changed: &[C::Field]) -> Result<(), Error> {
+        self.notify_changed_indexes(&indexes(changed))
+    }
+
+    /// Notify the controller that the fields with the provided indexes have changed, as with
+    /// [`Self::notify_changed()`].
+    pub(crate) fn notify_changed_indexes(&self, changed: &[usize]) -> Result<(), Error> {
+        let origin = origin::current();
+
+        let controller_ref = self.0.borrow();
+
+        let controller = controller_ref
+            .as_ref()
+            .ok_or(Error::UnboundController)?
+            .borrow();
+
+        let mut pending = controller.pending.borrow_mut();
+        match pending.as_mut() {
+            // An update is already scheduled, so include these changes in it
+            Some(pending) => {
+                for changed in changed {
+                    match pending.iter_mut().find(|(field, _)| field == changed) {
+                        // Changed from multiple places, so it must be passed everywhere
+                        Some((_, existing)) if *existing != origin => *existing = None,
+                        Some(_) => (),
+                        None => pending.push((*changed, origin)),
+                    }
+                }
+            }
+            None => {
+                let controller_ref = self.clone();
+                controller
+                    .backend
+                    .schedule(move || controller_ref.flush())?;
+
+                *pending = Some(changed.iter().map(|changed| (*changed, origin)).collect());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawn `future` in the background with the [`Backend`], such as to fetch data in response
+    /// to an event. The future resolves to a function which updates the component, returning the
+    /// fields that changed, and the controller will be notified of them.
+    ///
+    /// If the component is detached before the future completes, the future is cancelled and the
+    /// component is never updated. Event handlers can instead take a [`Spawner`], so that the
+    /// component doesn't need to keep this reference.
+    pub fn spawn<Fut, U>(&self, future: Fut) -> Result<(), Error>
+    where
+        Fut: 'static + Future<Output = U>,
+        U: 'static + FnOnce(&mut C) -> Option<Vec<C::Field>>,
+    {
+        let controller = self.get_ref().ok_or(Error::UnboundController)?;
+        let controller_ref = self.clone();
+
+        let (task, future) = Task::new(async move {
+            let update = future.await;
+
+            let controller = controller_ref.get_ref().ok_or(Error::UnboundController)?;
+            let component = Rc::clone(&controller.borrow().component);
+            let changed = update(&mut component.borrow_mut());
+
+            match changed {
+                Some(changed) => controller_ref.notify_changed(&changed),
+                None => Ok(()),
+            }
+        });
+
+        let controller = controller.borrow();
+        controller.add_task(task);
+        controller.backend.spawn(future);
+
+        Ok(())
+    }
+
+    /// Get a [`Spawner`] for the controller, which can spawn futures as with [`Self::spawn()`].
+    /// The spawner only holds a weak reference to the controller, so unlike this reference it can
+    /// be kept within the component without keeping the component alive.
+    pub fn spawner(&self) -> Spawner<C> {
+        let controller_ref = Rc::downgrade(&self.0);
+
+        Spawner::new(move |future| {
+            let controller_ref = controller_ref.upgrade().ok_or(Error::UnboundController)?;
+
+            Self(controller_ref).spawn(future)
+        })
+    }
+
+    /// Immediately apply any changes that are waiting for a scheduled update, rather than waiting
+    /// for the update to run. Useful when the DOM must be up to date synchronously.
     ///
     /// Notably, this will update the fragment within the controller, and also trigger the
     /// `bound_update` for the controller, which is an optional closure passed in by the parent of
     /// the component. This has to be called from this method, as it's possible that the parent
     /// will attempt to gain a mutable borrow on the child, which cannot be done if the child is
     /// already mutably borrowed to run the update function.
-    pub fn notify_changed(&self, changed: &[usize]) {
-        let bound_update = {
+    pub fn flush(&self) -> Result<(), Error> {
+        let (id, changed, bound_update) = {
             let controller_ref = self.0.borrow();
 
             let controller = controller_ref
                 .as_ref()
-                .expect("controller to be present")
+                .ok_or(Error::UnboundController)?
                 .borrow();
 
-            controller.update_fragment(changed);
+            // Nothing to do if there are no changes (such as if already flushed)
+            let Some(pending) = controller.pending.borrow_mut().take() else {
+                return Ok(());
+            };
+
+            let mut changed = pending.iter().map(|(field, _)| *field).collect::<Vec<_>>();
+
+            // Derived fields didn't originate from anywhere, so aren't included in the origins
+            controller.derive_changed(&mut changed);
+            let origins = pending
+                .into_iter()
+                .filter_map(|(field, origin)| Some((field, origin?)))
+                .collect();
 
-            controller.bound_update.clone()
+            origin::with_updating(origins, || controller.update_fragment(&changed))?;
+
+            (controller.id(), changed, controller.bound_update.clone())
         };
 
-        // Update bound parent
+        // Update bound parent, marking any changes it makes as originating from this controller
         if let Some(bound_update) = bound_update {
-            bound_update(changed);
+            origin::with_origin(id, || bound_update(&changed));
         }
+
+        Ok(())
+    }
+}
+
+impl<C, B> Default for ControllerRef<C, B>
+where
+    C: Component + ?Sized + 'static,
+    B: Backend,
+{
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl<C> Clone for ControllerRef<C>
+impl<C, B> Clone for ControllerRef<C, B>
 where
     C: Component + ?Sized,
+    B: Backend,
 {
     fn clone(&self) -> Self {
         Self(Rc::clone(&self.0))
@@ -0,0 +1,52 @@
+use std::{future::Future, pin::Pin, rc::Rc};
+
+use crate::component::Component;
+use crate::Error;
+
+/// A function which updates a component once a spawned future has completed, returning the fields
+/// that changed.
+type UpdateComponentFn<C> = Box<dyn FnOnce(&mut C) -> Option<Vec<<C as Component>::Field>>>;
+
+/// A spawned future, resolving to the function which updates the component.
+pub(super) type SpawnedFuture<C> = Pin<Box<dyn Future<Output = UpdateComponentFn<C>>>>;
+
+/// A function which spawns a future for a component.
+type SpawnFn<C> = Rc<dyn Fn(SpawnedFuture<C>) -> Result<(), Error>>;
+
+/// A handle for spawning futures on behalf of a component, which an `#[event]` handler can take as
+/// an argument (eg `fn fetch(&mut self, spawner: Spawner<Self>)`). Futures are spawned with
+/// [`super::ControllerRef::spawn()`], so they're cancelled if the component is detached before
+/// they complete.
+///
+/// The handle only holds a weak reference to the controller (see
+/// [`super::ControllerRef::spawner()`]), so it won't keep the component alive if it is stored
+/// within the component.
+pub struct Spawner<C: Component + ?Sized>(SpawnFn<C>);
+
+impl<C: Component + ?Sized + 'static> Spawner<C> {
+    /// Create a handle which spawns futures with `spawn`.
+    pub(super) fn new<F>(spawn: F) -> Self
+    where
+        F: 'static + Fn(SpawnedFuture<C>) -> Result<(), Error>,
+    {
+        Self(Rc::new(spawn))
+    }
+
+    /// Spawn `future` in the background, as with [`super::ControllerRef::spawn()`]. The future
+    /// resolves to a function which updates the component, returning the fields that changed.
+    pub fn spawn<Fut, U>(&self, future: Fut) -> Result<(), Error>
+    where
+        Fut: 'static + Future<Output = U>,
+        U: 'static + FnOnce(&mut C) -> Option<Vec<C::Field>>,
+    {
+        (self.0)(Box::pin(async move {
+            Box::new(future.await) as UpdateComponentFn<C>
+        }))
+    }
+}
+
+impl<C: Component + ?Sized> Clone for Spawner<C> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
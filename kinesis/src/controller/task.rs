@@ -0,0 +1,93 @@
+//! Futures spawned on behalf of a component, which can be cancelled if the component is detached
+//! before they complete.
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::Error;
+
+/// State shared between a [`Task`] and its [`Cancellable`] future.
+#[derive(Default)]
+struct TaskState {
+    /// Whether the task has been cancelled.
+    cancelled: Cell<bool>,
+
+    /// Whether the future has completed (or was cancelled).
+    finished: Cell<bool>,
+
+    /// The waker from the most recent poll, so that a pending future can be woken to be dropped
+    /// once cancelled.
+    waker: RefCell<Option<Waker>>,
+}
+
+/// A handle to a spawned future, allowing for it to be cancelled.
+pub(crate) struct Task(Rc<TaskState>);
+
+impl Task {
+    /// Wrap `future` so that it can be cancelled with the returned [`Task`].
+    pub(crate) fn new<F>(future: F) -> (Self, Cancellable<F>)
+    where
+        F: Future<Output = Result<(), Error>>,
+    {
+        let state = Rc::new(TaskState::default());
+
+        (
+            Self(Rc::clone(&state)),
+            Cancellable {
+                future: Box::pin(future),
+                state,
+            },
+        )
+    }
+
+    /// Cancel the future. It will never be polled again, so anything after its current `.await`
+    /// will not run.
+    pub(crate) fn cancel(&self) {
+        self.0.cancelled.set(true);
+
+        // Wake the future so that the executor drops it, rather than waiting on it forever
+        if let Some(waker) = self.0.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Whether the future has completed, or was cancelled.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.0.finished.get()
+    }
+}
+
+/// A future which completes early (without polling the wrapped future) once its [`Task`] is
+/// cancelled.
+pub(crate) struct Cancellable<F> {
+    future: Pin<Box<F>>,
+    state: Rc<TaskState>,
+}
+
+impl<F> Future for Cancellable<F>
+where
+    F: Future<Output = Result<(), Error>>,
+{
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.cancelled.get() {
+            self.state.finished.set(true);
+            return Poll::Ready(Ok(()));
+        }
+
+        let result = self.future.as_mut().poll(cx);
+
+        match result {
+            Poll::Ready(_) => self.state.finished.set(true),
+            Poll::Pending => *self.state.waker.borrow_mut() = Some(cx.waker().clone()),
+        }
+
+        result
+    }
+}
@@ -0,0 +1,103 @@
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::fragment::{Cursor, Location};
+use crate::Error;
+
+/// A function that returns the current value of an attribute, or [`None`] if the attribute should
+/// be removed.
+pub type GetAttributeFn = Box<dyn Fn() -> Option<String>>;
+
+/// An attribute on a static element which is recomputed whenever one of its dependencies change.
+/// It doesn't mount any nodes itself, instead it will use the parent of the [`Location`] it is
+/// mounted to as the element to set the attribute on.
+pub struct Attribute<B: Backend> {
+    /// The name of the attribute to set.
+    name: String,
+
+    /// Dependencies of the attribute, so that it is only recomputed when required.
+    dependencies: Vec<usize>,
+
+    /// A function that will return the value of the attribute for the given context.
+    get_value: GetAttributeFn,
+
+    /// The element that the attribute is set on, which is only present whilst mounted.
+    element: Option<B::Node>,
+
+    /// The value that is currently set on the element.
+    value: Option<String>,
+}
+
+impl<B: Backend> Attribute<B> {
+    /// Create a new attribute with the provided `get_value` function.
+    pub fn new(name: &str, dependencies: &[usize], get_value: GetAttributeFn) -> Self {
+        Self {
+            name: name.to_string(),
+            dependencies: dependencies.to_vec(),
+            get_value,
+            element: None,
+            value: None,
+        }
+    }
+
+    /// Helper function to compute the value of the attribute, and apply it to the element if it
+    /// has changed.
+    fn apply(&mut self) -> Result<(), Error> {
+        let value = (self.get_value)();
+
+        if value == self.value {
+            return Ok(());
+        }
+
+        if let Some(element) = &self.element {
+            match &value {
+                Some(value) => B::set_attribute(element, &self.name, value)?,
+                None => B::remove_attribute(element, &self.name)?,
+            }
+        }
+
+        self.value = value;
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Attribute<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.element = Some(location.get_parent().clone());
+        self.value = None;
+
+        self.apply()
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        // The attribute may already be set, however it must still be applied in case the value
+        // differs
+        self.element = Some(cursor.get_parent().clone());
+        self.value = None;
+
+        self.apply()
+    }
+
+    fn detach(&mut self, _top_level: bool) -> Result<(), Error> {
+        // The element will be detached by the fragment, so there is nothing to remove
+        self.element = None;
+
+        Ok(())
+    }
+
+    fn relocate(&mut self, _location: &Location<B>) -> Result<(), Error> {
+        // Nothing is mounted, so there is nothing to move
+        Ok(())
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        if changed
+            .iter()
+            .any(|changed| self.dependencies.contains(changed))
+        {
+            self.apply()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::controller::task::Task;
+use crate::event_registry::EventRegistry;
+use crate::fragment::{Cursor, Fragment, FragmentBuilder, Location};
+use crate::Error;
+
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+/// A function that starts a future, which resolves to the [`FragmentBuilder`] to show once it
+/// completes.
+pub type GetAwaitFn<B> = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = FragmentBuilder<B>>>>>;
+
+/// A function that returns the [`FragmentBuilder`] to show whilst the future is pending.
+pub type GetPendingFn<B> = Box<dyn Fn() -> FragmentBuilder<B>>;
+
+/// Content that is swapped as the state of the future changes, which is shared with the spawned
+/// future so that it can be swapped once the future completes.
+struct AwaitContent<B: Backend> {
+    /// A reference to the [`Backend`], required to build new [`Fragment`]s.
+    backend: B,
+
+    /// The currently mounted fragment, if there is one.
+    fragment: Option<Fragment<B>>,
+
+    /// A reference to an anchor within the DOM, which the fragment is rendered before.
+    anchor: B::Node,
+
+    event_registry: Rc<RefCell<EventRegistry<B>>>,
+}
+
+impl<B: Backend> AwaitContent<B> {
+    /// Helper function to detach the mounted fragment, and replace it with one built from
+    /// `builder`.
+    fn swap(&mut self, builder: FragmentBuilder<B>) -> Result<(), Error> {
+        if let Some(mut fragment) = self.fragment.take() {
+            fragment.detach(true)?;
+        }
+
+        let mut fragment = builder.build(&self.backend, &self.event_registry)?;
+        fragment.mount(&Location::anchor(&self.anchor)?)?;
+        fragment.full_update()?;
+
+        self.fragment = Some(fragment);
+
+        Ok(())
+    }
+}
+
+/// Renders a pending fragment whilst a future runs, which is swapped for the fragment that the
+/// future resolves to once it completes. The future is re-run whenever a dependency changes, and
+/// any earlier run that is still pending is cancelled so that its result is never shown.
+///
+/// When rendering to a string the future never completes, so the pending fragment is rendered and
+/// then adopted whilst hydrating.
+pub struct Await<B: Backend> {
+    /// The mounted content, shared with the spawned future.
+    content: Rc<RefCell<AwaitContent<B>>>,
+
+    /// Fields which will cause the future to be re-run when they change.
+    dependencies: Vec<usize>,
+
+    /// Starts the future, producing the fragment to show once it resolves.
+    get_future: GetAwaitFn<B>,
+
+    /// Produces the fragment to show whilst the future is pending.
+    get_pending: GetPendingFn<B>,
+
+    /// The most recently spawned future, if it has been spawned.
+    task: Option<Task>,
+}
+
+impl<B: Backend> Await<B> {
+    /// Create a new instance, which will run `get_future` and show the fragment from
+    /// `get_pending` until it resolves.
+    pub fn new(
+        backend: &B,
+        dependencies: &[usize],
+        get_future: GetAwaitFn<B>,
+        get_pending: GetPendingFn<B>,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Self {
+        Self {
+            content: Rc::new(RefCell::new(AwaitContent {
+                backend: backend.clone(),
+                fragment: None,
+                anchor: backend.create_text(""),
+                event_registry: Rc::clone(event_registry),
+            })),
+            dependencies: dependencies.to_vec(),
+            get_future,
+            get_pending,
+            task: None,
+        }
+    }
+
+    /// Helper function to cancel the previous future (if it is still pending), returning whether
+    /// it was still pending.
+    fn cancel(&mut self) -> bool {
+        self.task.take().is_some_and(|task| {
+            let pending = !task.is_finished();
+            task.cancel();
+
+            pending
+        })
+    }
+
+    /// Helper function to spawn a new run of the future, which will swap in the resolved fragment
+    /// once it completes. The future is only started once it is first polled, so a run that is
+    /// cancelled straight away (such as by a full update after mounting) never starts it.
+    fn spawn(&mut self) {
+        let content = Rc::downgrade(&self.content);
+        let get_future = Rc::clone(&self.get_future);
+
+        let (task, future) = Task::new(async move {
+            let builder = get_future().await;
+
+            match content.upgrade() {
+                Some(content) => content.borrow_mut().swap(builder),
+                None => Ok(()),
+            }
+        });
+
+        self.task = Some(task);
+        self.content.borrow().backend.spawn(future);
+    }
+
+    /// Helper function to re-run the future, showing the pending fragment until it resolves.
+    fn run(&mut self) -> Result<(), Error> {
+        // The pending fragment will already be shown if the previous run hadn't resolved
+        if !self.cancel() {
+            self.content.borrow_mut().swap((self.get_pending)())?;
+        }
+
+        self.spawn();
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Await<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        location.mount(&self.content.borrow().anchor)?;
+
+        self.run()
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        {
+            let mut content = self.content.borrow_mut();
+
+            // The pending fragment will have been rendered before the anchor
+            let mut fragment =
+                (self.get_pending)().build(&content.backend, &content.event_registry)?;
+            fragment.hydrate(cursor)?;
+
+            content.fragment = Some(fragment);
+            content.anchor = cursor.take_text(Some(""))?;
+        }
+
+        self.spawn();
+
+        Ok(())
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.cancel();
+
+        let mut content = self.content.borrow_mut();
+        if let Some(mut fragment) = content.fragment.take() {
+            fragment.detach(top_level)?;
+        }
+
+        B::remove(&content.anchor)
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        let mut content = self.content.borrow_mut();
+        if let Some(fragment) = content.fragment.as_mut() {
+            fragment.relocate(location)?;
+        }
+
+        location.mount(&content.anchor)
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        if changed
+            .iter()
+            .any(|changed| self.dependencies.contains(changed))
+        {
+            return self.run();
+        }
+
+        // The mounted fragment may depend on other fields
+        match self.content.borrow_mut().fragment.as_mut() {
+            Some(fragment) => fragment.update(changed),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::html;
+
+    use std::{cell::Cell, future::poll_fn, task::Poll};
+
+    /// The result of each run of the future, which stays pending until the result is set.
+    type Results = Rc<RefCell<Vec<Rc<Cell<Option<Result<&'static str, &'static str>>>>>>>;
+
+    /// Helper function to mount a fragment containing an await block, which depends on `0`.
+    fn mount(results: &Results) -> (MemoryBackend, Fragment<MemoryBackend>) {
+        let backend = MemoryBackend::new();
+
+        let builder: FragmentBuilder<MemoryBackend> = html! {
+            {#await_or_else(
+                &[0],
+                {
+                    let results = Rc::clone(results);
+                    move || {
+                        let result = Rc::new(Cell::new(None));
+                        results.borrow_mut().push(Rc::clone(&result));
+
+                        poll_fn(move |_| match result.get() {
+                            Some(result) => Poll::Ready(result),
+                            None => Poll::Pending,
+                        })
+                    }
+                },
+                || html! { "pending" },
+                |result: &'static str| html! { {result} },
+                |error: &'static str| html! { {format!("failed: {error}")} }
+            )}
+        };
+
+        let mut fragment = builder
+            .build(&backend, &EventRegistry::new(|_, _| Ok(()), |_| Ok(())))
+            .unwrap();
+        fragment.mount(&Location::parent(&backend.body())).unwrap();
+        fragment.full_update().unwrap();
+        backend.run_scheduled().unwrap();
+
+        (backend, fragment)
+    }
+
+    #[test]
+    fn stale_result_is_ignored() {
+        let results = Results::default();
+        let (backend, mut fragment) = mount(&results);
+
+        fragment.update(&[0]).unwrap();
+        backend.run_scheduled().unwrap();
+        assert_eq!(results.borrow().len(), 2);
+
+        // The previous run resolves after the newer run started
+        results.borrow()[0].set(Some(Ok("stale")));
+        backend.run_scheduled().unwrap();
+        assert_eq!(backend.body().text_content(), "pending");
+
+        results.borrow()[1].set(Some(Ok("latest")));
+        backend.run_scheduled().unwrap();
+        assert_eq!(backend.body().text_content(), "latest");
+    }
+
+    #[test]
+    fn result_after_detach_is_ignored() {
+        let results = Results::default();
+        let (backend, mut fragment) = mount(&results);
+
+        fragment.detach(true).unwrap();
+        results.borrow()[0].set(Some(Ok("detached")));
+        backend.run_scheduled().unwrap();
+
+        assert!(backend.body().children().is_empty());
+        assert_eq!(backend.spawned(), 0);
+    }
+
+    #[test]
+    fn error_is_rendered() {
+        let results = Results::default();
+        let (backend, _fragment) = mount(&results);
+
+        results.borrow()[0].set(Some(Err("offline")));
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(backend.body().text_content(), "failed: offline");
+    }
+
+    #[test]
+    fn error_without_builder_renders_nothing() {
+        let backend = MemoryBackend::new();
+        let builder: FragmentBuilder<MemoryBackend> = html! {
+            {#await(
+                &[],
+                || async { Err::<&str, _>("offline") },
+                || html! { "pending" },
+                |result: &str| html! { {result} }
+            )}
+        };
+
+        let mut fragment = builder
+            .build(&backend, &EventRegistry::new(|_, _| Ok(()), |_| Ok(())))
+            .unwrap();
+        fragment.mount(&Location::parent(&backend.body())).unwrap();
+        fragment.full_update().unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(backend.body().text_content(), "");
+    }
+}
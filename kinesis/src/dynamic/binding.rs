@@ -0,0 +1,188 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::Dynamic;
+use crate::backend::{Backend, PropertyValue};
+use crate::event::EventOptions;
+use crate::event_registry::NotifyChangedFn;
+use crate::fragment::{Cursor, Location};
+use crate::Error;
+
+/// A function that returns the current value of a bound field.
+pub type GetBindingFn = Box<dyn Fn() -> PropertyValue>;
+
+/// A function that writes a value from the element back into a bound field.
+pub type SetBindingFn = Rc<dyn Fn(PropertyValue)>;
+
+/// The property of a form element that a [`Binding`] keeps in sync.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingKind {
+    /// The `value` of an `<input>`, `<textarea>` or `<select>`, which is read on `input`.
+    Value,
+
+    /// The `checked` state of a checkbox or radio button, which is read on `change`.
+    Checked,
+}
+
+impl BindingKind {
+    /// The name of the property that is bound.
+    pub fn property(&self) -> &'static str {
+        match self {
+            Self::Value => "value",
+            Self::Checked => "checked",
+        }
+    }
+
+    /// The event type that is fired when the property is changed by the user.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::Value => "input",
+            Self::Checked => "change",
+        }
+    }
+}
+
+/// Two-way binding between a field of a component and a property of a static form element. The
+/// property is set whenever the bound field changes, and when the user changes the property the
+/// new value is written back into the component, notifying the controller that the bound field
+/// has changed. As with [`super::Attribute`], it doesn't mount any nodes itself, instead it will
+/// use the parent of the [`Location`] it is mounted to as the element.
+pub struct Binding<B: Backend> {
+    /// The property that is bound.
+    kind: BindingKind,
+
+    /// The identifier of the bound field.
+    id: usize,
+
+    /// A function that will return the value of the bound field.
+    get_value: GetBindingFn,
+
+    /// A function that will write a value back into the bound field.
+    set_value: SetBindingFn,
+
+    /// Callback to notify the controller that the bound field has changed.
+    notify_changed: NotifyChangedFn,
+
+    /// The element that the property is set on, which is only present whilst mounted.
+    element: Option<B::Node>,
+
+    /// The element that the listener has been bound to, so that it is only bound once.
+    listening: Option<B::Node>,
+
+    /// The value that is currently set on the element. Shared with the listener, so that a value
+    /// from the user won't be set on the element again.
+    value: Rc<RefCell<Option<PropertyValue>>>,
+}
+
+impl<B: Backend> Binding<B> {
+    /// Create a new binding for the field `id`.
+    pub fn new(
+        kind: BindingKind,
+        id: usize,
+        get_value: GetBindingFn,
+        set_value: SetBindingFn,
+        notify_changed: NotifyChangedFn,
+    ) -> Self {
+        Self {
+            kind,
+            id,
+            get_value,
+            set_value,
+            notify_changed,
+            element: None,
+            listening: None,
+            value: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Helper function to begin using `element`, binding the listener to it if it hasn't already
+    /// been bound, and applying the current value.
+    fn attach(&mut self, element: &B::Node) -> Result<(), Error> {
+        if self.listening.is_none() {
+            let listener = B::create_listener({
+                let kind = self.kind;
+                let id = self.id;
+                let element = element.clone();
+                let set_value = Rc::clone(&self.set_value);
+                let notify_changed = Rc::clone(&self.notify_changed);
+                let current = Rc::clone(&self.value);
+
+                move |_| {
+                    let Some(value) = B::property(&element, kind.property()) else {
+                        return Ok(());
+                    };
+
+                    *current.borrow_mut() = Some(value.clone());
+                    set_value(value);
+
+                    notify_changed(&[id])
+                }
+            });
+
+            B::add_listener(
+                element,
+                self.kind.event_type(),
+                &listener,
+                &EventOptions::default(),
+            )?;
+            self.listening = Some(element.clone());
+        }
+
+        self.element = Some(element.clone());
+        *self.value.borrow_mut() = None;
+
+        self.apply()
+    }
+
+    /// Helper function to compute the value of the bound field, and apply it to the element if it
+    /// has changed. The value is only kept once the element reflects it, as a `<select>` will
+    /// ignore a value that doesn't match any of its options (such as before they are mounted).
+    fn apply(&mut self) -> Result<(), Error> {
+        let Some(element) = &self.element else {
+            return Ok(());
+        };
+
+        let value = (self.get_value)();
+        if self.value.borrow().as_ref() == Some(&value) {
+            return Ok(());
+        }
+
+        let property = self.kind.property();
+        B::set_property(element, property, &value)?;
+
+        *self.value.borrow_mut() =
+            (B::property(element, property).as_ref() == Some(&value)).then_some(value);
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Binding<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.attach(location.get_parent())
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.attach(cursor.get_parent())
+    }
+
+    fn detach(&mut self, _top_level: bool) -> Result<(), Error> {
+        // The element will be detached by the fragment, so there is nothing to remove
+        self.element = None;
+
+        Ok(())
+    }
+
+    fn relocate(&mut self, _location: &Location<B>) -> Result<(), Error> {
+        // Nothing is mounted, so there is nothing to move
+        Ok(())
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        // Retry a value which wasn't taken by the element, as its options may have changed
+        if changed.contains(&self.id) || self.value.borrow().is_none() {
+            self.apply()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,92 +1,118 @@
 use super::Dynamic;
+use crate::backend::Backend;
 use crate::event_registry::EventRegistry;
-use crate::fragment::{Fragment, FragmentBuilder, Location};
+use crate::fragment::{Cursor, Fragment, FragmentBuilder, Location};
+use crate::Error;
 
 use std::{cell::RefCell, rc::Rc};
-use web_sys::{Document, Node as WsNode};
 
 /// A function that returns an [`Iterator`] of [`FragmentBuilder`]s, for the given context.
-pub type GetIterFn = Box<dyn Fn() -> Box<dyn std::iter::Iterator<Item = FragmentBuilder>>>;
+pub type GetIterFn<B> = Box<dyn Fn() -> Box<dyn std::iter::Iterator<Item = FragmentBuilder<B>>>>;
 
 /// Generates [`Fragment`]s for each item of an [`std::iter::Iterator`] dynamically.
-pub struct Iterator {
-    /// A reference to [`Document`], which is required in order to create new [`Fragment`]s.
-    document: Document,
+pub struct Iterator<B: Backend> {
+    /// A reference to the [`Backend`], which is required in order to create new [`Fragment`]s.
+    backend: B,
 
     /// A function that will return an [`std::iter::Iterator`] of [`FragmentBuilder`]s for the
     /// given context.
-    get_iter: GetIterFn,
+    get_iter: GetIterFn<B>,
 
     /// If there are mounted fragments, their references will be contained here. This is primarily
     /// to allow for proper detaching of the [`Fragment`]s.
-    mounted_fragments: Option<Vec<Fragment>>,
+    mounted_fragments: Option<Vec<Fragment<B>>>,
 
     /// A reference to an anchor within the DOM. Items within the iterator will be rendered at this
     /// location.
-    anchor: WsNode,
+    anchor: B::Node,
 
-    event_registry: Rc<RefCell<EventRegistry>>,
+    event_registry: Rc<RefCell<EventRegistry<B>>>,
 }
 
-impl Iterator {
-    /// Create a new iterator with the provided `get_iter` function. Requires a reference to
-    /// [`Document`] in order to clone and store it for future use.
+impl<B: Backend> Iterator<B> {
+    /// Create a new iterator with the provided `get_iter` function. Requires a reference to the
+    /// [`Backend`] in order to clone and store it for future use.
     pub fn new(
-        document: &Document,
-        get_iter: GetIterFn,
-        event_registry: &Rc<RefCell<EventRegistry>>,
+        backend: &B,
+        get_iter: GetIterFn<B>,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
     ) -> Self {
         Self {
-            document: document.clone(),
+            backend: backend.clone(),
             get_iter,
             mounted_fragments: None,
-            anchor: document.create_text_node("").into(),
+            anchor: backend.create_text(""),
             event_registry: Rc::clone(event_registry),
         }
     }
 
     /// Helper function to trigger each of the mounted [`Fragment`]s to detach, propagating
     /// `top_level` through.
-    fn detach_fragments(&mut self, top_level: bool) {
+    fn detach_fragments(&mut self, top_level: bool) -> Result<(), Error> {
         self.mounted_fragments
             .take()
             .into_iter()
             .flatten()
-            .for_each(|mut fragment| fragment.detach(top_level));
+            .try_for_each(|mut fragment| fragment.detach(top_level))
     }
 }
 
-impl Dynamic for Iterator {
-    fn mount(&mut self, location: &Location) {
-        location.mount(&self.anchor);
+impl<B: Backend> Dynamic<B> for Iterator<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        location.mount(&self.anchor)
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.detach_fragments(top_level);
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        // Items are rendered before the anchor
+        self.mounted_fragments = Some(
+            (self.get_iter)()
+                .map(|builder| {
+                    let mut fragment = builder.build(&self.backend, &self.event_registry)?;
+                    fragment.hydrate(cursor)?;
+
+                    Ok(fragment)
+                })
+                .collect::<Result<_, Error>>()?,
+        );
+
+        self.anchor = cursor.take_text(Some(""))?;
+
+        Ok(())
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.detach_fragments(top_level)?;
+
+        B::remove(&self.anchor)
+    }
 
-        self.anchor
-            .parent_node()
-            .expect("node to have parent")
-            .remove_child(&self.anchor)
-            .expect("to remove child");
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.mounted_fragments
+            .iter_mut()
+            .flatten()
+            .try_for_each(|fragment| fragment.relocate(location))?;
+
+        location.mount(&self.anchor)
     }
 
-    fn update(&mut self, changed: &[usize]) {
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
         // Detach all current mounted fragments (top level as their parent won't be removed)
-        self.detach_fragments(true);
+        self.detach_fragments(true)?;
 
         // Create new fragments
         self.mounted_fragments = Some(
             (self.get_iter)()
                 .map(|builder| {
-                    let mut fragment = builder.build(&self.document, &self.event_registry);
+                    let mut fragment = builder.build(&self.backend, &self.event_registry)?;
 
-                    fragment.mount(&Location::anchor(&self.anchor));
-                    fragment.update(changed);
+                    fragment.mount(&Location::anchor(&self.anchor)?)?;
+                    fragment.update(changed)?;
 
-                    fragment
+                    Ok(fragment)
                 })
-                .collect(),
+                .collect::<Result<_, Error>>()?,
         );
+
+        Ok(())
     }
 }
@@ -0,0 +1,337 @@
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::event_registry::EventRegistry;
+use crate::fragment::{Cursor, Fragment, FragmentBuilder, Location};
+use crate::Error;
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    rc::Rc,
+};
+
+/// A function that returns an [`Iterator`] of keys and [`FragmentBuilder`]s, for the given
+/// context.
+pub type GetKeyedIterFn<K, B> =
+    Box<dyn Fn() -> Box<dyn std::iter::Iterator<Item = (K, FragmentBuilder<B>)>>>;
+
+/// A [`Fragment`] that has been rendered for a key.
+struct KeyedFragment<K, B: Backend> {
+    key: K,
+
+    /// Node mounted directly before the fragment, so that other items can be inserted before
+    /// this item.
+    marker: B::Node,
+
+    fragment: Fragment<B>,
+}
+
+/// Generates [`Fragment`]s for each item of an [`std::iter::Iterator`] dynamically, re-using the
+/// previously rendered [`Fragment`] for any item with a matching key.
+pub struct KeyedIterator<K, B: Backend> {
+    /// A reference to the [`Backend`], which is required in order to create new [`Fragment`]s.
+    backend: B,
+
+    /// A function that will return an [`std::iter::Iterator`] of keys and [`FragmentBuilder`]s
+    /// for the given context.
+    get_iter: GetKeyedIterFn<K, B>,
+
+    /// Currently mounted fragments, in the order that they appear in the DOM.
+    mounted_fragments: Vec<KeyedFragment<K, B>>,
+
+    /// A reference to an anchor within the DOM. Items within the iterator will be rendered at this
+    /// location.
+    anchor: B::Node,
+
+    event_registry: Rc<RefCell<EventRegistry<B>>>,
+}
+
+impl<K, B> KeyedIterator<K, B>
+where
+    K: Eq + Hash + Clone,
+    B: Backend,
+{
+    /// Create a new keyed iterator with the provided `get_iter` function. Requires a reference to
+    /// the [`Backend`] in order to clone and store it for future use.
+    pub fn new(
+        backend: &B,
+        get_iter: GetKeyedIterFn<K, B>,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Self {
+        Self {
+            backend: backend.clone(),
+            get_iter,
+            mounted_fragments: Vec::new(),
+            anchor: backend.create_text(""),
+            event_registry: Rc::clone(event_registry),
+        }
+    }
+}
+
+impl<K, B> Dynamic<B> for KeyedIterator<K, B>
+where
+    K: Eq + Hash + Clone,
+    B: Backend,
+{
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        location.mount(&self.anchor)
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        // Each item is rendered as its marker followed by its fragment, all before the anchor
+        let mut seen = HashSet::new();
+        self.mounted_fragments = (self.get_iter)()
+            .filter(|(key, _)| seen.insert(key.clone()))
+            .map(|(key, builder)| {
+                let marker = cursor.take_text(Some(""))?;
+
+                let mut fragment = builder.build(&self.backend, &self.event_registry)?;
+                fragment.hydrate(cursor)?;
+
+                Ok(KeyedFragment {
+                    key,
+                    marker,
+                    fragment,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        self.anchor = cursor.take_text(Some(""))?;
+
+        Ok(())
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.mounted_fragments.drain(..).try_for_each(|mut item| {
+            item.fragment.detach(top_level)?;
+            B::remove(&item.marker)
+        })?;
+
+        B::remove(&self.anchor)
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.mounted_fragments.iter_mut().try_for_each(|item| {
+            location.mount(&item.marker)?;
+            item.fragment.relocate(location)
+        })?;
+
+        location.mount(&self.anchor)
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        let mounted = self
+            .mounted_fragments
+            .iter()
+            .map(|item| item.key.clone())
+            .collect::<HashSet<_>>();
+
+        // Build a fragment for each new item before anything is changed, so that an error leaves
+        // the mounted fragments in place. Any items with a duplicate key are ignored.
+        let mut seen = HashSet::new();
+        let built = (self.get_iter)()
+            .filter(|(key, _)| seen.insert(key.clone()))
+            .map(|(key, builder)| {
+                if mounted.contains(&key) {
+                    return Ok((key, None));
+                }
+
+                let item = KeyedFragment {
+                    key: key.clone(),
+                    marker: self.backend.create_text(""),
+                    fragment: builder.build(&self.backend, &self.event_registry)?,
+                };
+
+                Ok((key, Some(item)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Index the currently mounted fragments by their key, remembering their position
+        let mut existing = self
+            .mounted_fragments
+            .drain(..)
+            .enumerate()
+            .map(|(position, item)| (item.key.clone(), (position, item)))
+            .collect::<HashMap<_, _>>();
+
+        // Match up each item with its existing fragment, if it wasn't built
+        let mut items = built
+            .into_iter()
+            .map(|(key, item)| match item {
+                Some(item) => (None, item),
+                None => {
+                    let (position, item) = existing
+                        .remove(&key)
+                        .expect("fragment to be mounted for key");
+                    (Some(position), item)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Anything left over was removed
+        existing.into_values().try_for_each(|(_, mut item)| {
+            item.fragment.detach(true)?;
+            B::remove(&item.marker)
+        })?;
+
+        // Kept fragments that are still in the same relative order don't need to be moved
+        let stable = stable_positions(
+            &items
+                .iter()
+                .map(|(position, _)| *position)
+                .collect::<Vec<_>>(),
+        );
+
+        // Work backwards so that each item can be placed before the item following it
+        let mut next = self.anchor.clone();
+        for (i, (position, item)) in items.iter_mut().enumerate().rev() {
+            let location = Location::anchor(&next)?;
+
+            if position.is_none() {
+                location.mount(&item.marker)?;
+                item.fragment.mount(&location)?;
+            } else if !stable[i] {
+                location.mount(&item.marker)?;
+                item.fragment.relocate(&location)?;
+            }
+
+            item.fragment.update(changed)?;
+
+            next = item.marker.clone();
+        }
+
+        self.mounted_fragments = items.into_iter().map(|(_, item)| item).collect();
+
+        Ok(())
+    }
+}
+
+/// Determine which items don't need to be moved, given the previous position of each item (or
+/// [`None`] if the item is new). This is the longest increasing subsequence of the previous
+/// positions, as moving everything else into place around them is the least amount of moves.
+fn stable_positions(previous: &[Option<usize>]) -> Vec<bool> {
+    // Index into `previous` of the smallest tail of an increasing subsequence of each length
+    let mut tails: Vec<usize> = Vec::new();
+
+    // Index into `previous` of the item before each item in its subsequence
+    let mut predecessors = vec![None; previous.len()];
+
+    for (i, position) in previous.iter().enumerate() {
+        let Some(position) = position else {
+            continue;
+        };
+
+        let length = tails.partition_point(|&tail| previous[tail] < Some(*position));
+        predecessors[i] = length.checked_sub(1).map(|length| tails[length]);
+
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    // Walk back through the longest subsequence, marking each item as stable
+    let mut stable = vec![false; previous.len()];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        stable[i] = true;
+        current = predecessors[i];
+    }
+
+    stable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MemoryBackend, MemoryNode, MemoryNodeKind};
+    use crate::html;
+
+    /// Items rendered by a keyed iterator, which can be changed between updates.
+    type Items = Rc<RefCell<Vec<(usize, &'static str)>>>;
+
+    /// Helper function to mount a keyed iterator rendering a `<p>` for each item.
+    fn mount(items: &Items) -> (MemoryNode, KeyedIterator<usize, MemoryBackend>) {
+        let backend = MemoryBackend::new();
+        let items = Rc::clone(items);
+
+        let mut iterator = KeyedIterator::new(
+            &backend,
+            Box::new(move || {
+                Box::new(
+                    items
+                        .borrow()
+                        .clone()
+                        .into_iter()
+                        .map(|(key, text)| (key, html! { <p>{text}</p> })),
+                )
+            }),
+            &EventRegistry::new(|_, _| Ok(()), |_| Ok(())),
+        );
+        iterator.mount(&Location::parent(&backend.body())).unwrap();
+        iterator.update(&[]).unwrap();
+
+        (backend.body(), iterator)
+    }
+
+    /// Helper function to get each of the rendered elements.
+    fn elements(parent: &MemoryNode) -> Vec<MemoryNode> {
+        parent
+            .children()
+            .into_iter()
+            .filter(|child| matches!(child.kind(), MemoryNodeKind::Element(_)))
+            .collect()
+    }
+
+    #[test]
+    fn update_reuses_fragments_by_key() {
+        let items = Rc::new(RefCell::new(vec![(0, "a"), (1, "b"), (2, "c")]));
+        let (parent, mut iterator) = mount(&items);
+        let before = elements(&parent);
+
+        // Shuffled, with a duplicate key which is ignored
+        *items.borrow_mut() = vec![(2, "c"), (1, "b"), (2, "x"), (0, "a")];
+        iterator.update(&[]).unwrap();
+
+        let after = elements(&parent);
+        assert_eq!(parent.text_content(), "cba");
+        assert!(after == [before[2].clone(), before[1].clone(), before[0].clone()]);
+
+        // Removed from the middle, and inserted at the front
+        *items.borrow_mut() = vec![(3, "d"), (2, "c"), (0, "a")];
+        iterator.update(&[]).unwrap();
+
+        assert_eq!(parent.text_content(), "dca");
+        assert!(elements(&parent)[1..] == [before[2].clone(), before[0].clone()]);
+    }
+
+    #[test]
+    fn stable_positions_keeps_longest_increasing_subsequence() {
+        for (previous, expected) in [
+            // Unchanged
+            (vec![Some(0), Some(1), Some(2)], vec![true, true, true]),
+            // Reversed
+            (vec![Some(2), Some(1), Some(0)], vec![false, false, true]),
+            // Inserted at the front
+            (vec![None, Some(0), Some(1)], vec![false, true, true]),
+            // Removed from the middle
+            (vec![Some(0), Some(2), Some(3)], vec![true, true, true]),
+            // Moved from the back to the front
+            (
+                vec![Some(3), Some(0), Some(1), Some(2)],
+                vec![false, true, true, true],
+            ),
+            // Shuffled, along with a new item
+            (
+                vec![Some(1), None, Some(3), Some(0), Some(2), Some(4)],
+                vec![false, false, false, true, true, true],
+            ),
+            // Everything is new
+            (vec![None, None], vec![false, false]),
+        ] {
+            assert_eq!(stable_positions(&previous), expected, "{previous:?}");
+        }
+    }
+}
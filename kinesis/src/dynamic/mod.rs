@@ -1,21 +1,45 @@
+mod attribute;
+mod r#await;
+mod binding;
 mod iterator;
+mod keyed_iterator;
+mod portal;
+mod slot;
+mod text;
+mod tracked;
 mod update_proxy;
 
 use std::{cell::RefCell, rc::Rc};
 
-use super::Location;
+use crate::backend::Backend;
+use crate::fragment::{Cursor, Location};
+use crate::Error;
+pub use attribute::*;
+pub use binding::*;
 pub use iterator::*;
+pub use keyed_iterator::*;
+pub use portal::*;
+pub use r#await::*;
+pub use slot::*;
+pub use text::*;
+pub use tracked::*;
 pub use update_proxy::*;
 
 /// Used to implement things that can be rendered within the DOM. Must include the required
 /// functionality to mount nodes to the provided position, update itself as a result of any state
 /// cahnges, and to properly clean up after itself when detached from the DOM.
-pub trait Dynamic {
+pub trait Dynamic<B: Backend> {
     /// Mount self to the DOM as described by `location`. It is guarenteed that this method will
     /// only be called if not currently mounted, so this does not need to be checked. It also
     /// contains a `register_event` function, which can be used to propagate events back up to the
     /// controller.
-    fn mount(&mut self, location: &Location);
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error>;
+
+    /// Adopt nodes that already exist in the DOM from `cursor` (such as those from server
+    /// rendered markup), rather than creating and mounting new nodes. This is used in place of
+    /// [`Dynamic::mount()`], and should leave self in the same state as if it was mounted and then
+    /// updated. Any node that doesn't match what would have been mounted results in an error.
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error>;
 
     /// Detach self from the DOM. Should result in everying mounted in [`Dynamic::mount()`] being
     /// unmounted. `top_level` indicates that this item is at the top level of the item being
@@ -24,38 +48,59 @@ pub trait Dynamic {
     ///
     /// Note: Every node that is mounted doesn't need to be directly unmounted. If a node is a
     /// child of another node, then unmounting the parent will result in the child being unmounted.
-    fn detach(&mut self, top_level: bool);
+    fn detach(&mut self, top_level: bool) -> Result<(), Error>;
+
+    /// Move self to a new position in the DOM as described by `location`, without rebuilding or
+    /// re-creating any nodes. It is guarenteed that this method will only be called whilst
+    /// mounted. Only top level nodes need to be moved, as any children will move with them.
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error>;
 
     /// Update self due to a state change. Identifiers corresponding to the changed fields will be
     /// included as `changed`, however these should only be used to propagate changes to child
     /// [`super::Fragment`]s.
-    fn update(&mut self, changed: &[usize]);
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error>;
 }
 
-impl<D: Dynamic + ?Sized> Dynamic for Box<D> {
-    fn mount(&mut self, location: &Location) {
-        self.as_mut().mount(location);
+impl<B: Backend, D: Dynamic<B> + ?Sized> Dynamic<B> for Box<D> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.as_mut().mount(location)
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.as_mut().hydrate(cursor)
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.as_mut().detach(top_level)
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.as_mut().detach(top_level);
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.as_mut().relocate(location)
     }
 
-    fn update(&mut self, changed: &[usize]) {
-        self.as_mut().update(changed);
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        self.as_mut().update(changed)
     }
 }
 
-impl<D: Dynamic + ?Sized> Dynamic for Rc<RefCell<D>> {
-    fn mount(&mut self, location: &Location) {
-        self.borrow_mut().mount(location);
+impl<B: Backend, D: Dynamic<B> + ?Sized> Dynamic<B> for Rc<RefCell<D>> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.borrow_mut().mount(location)
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.borrow_mut().hydrate(cursor)
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.borrow_mut().detach(top_level)
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.borrow_mut().detach(top_level);
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.borrow_mut().relocate(location)
     }
 
-    fn update(&mut self, changed: &[usize]) {
-        self.borrow_mut().update(changed);
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        self.borrow_mut().update(changed)
     }
 }
@@ -0,0 +1,110 @@
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::fragment::{Cursor, Fragment, Location};
+use crate::Error;
+
+/// Where the content of a [`Portal`] is mounted.
+pub enum PortalTarget<B: Backend> {
+    /// Mount within the provided node.
+    Node(B::Node),
+
+    /// Mount within the first element matching a CSS selector (eg `body`, `#modals`), which is
+    /// found with [`Backend::query_selector()`] whenever the portal is mounted.
+    Selector(String),
+}
+
+impl<B: Backend> From<&str> for PortalTarget<B> {
+    fn from(selector: &str) -> Self {
+        Self::Selector(selector.to_string())
+    }
+}
+
+impl<B: Backend> From<String> for PortalTarget<B> {
+    fn from(selector: String) -> Self {
+        Self::Selector(selector)
+    }
+}
+
+/// Mounts a [`Fragment`] within a target elsewhere in the DOM (such as `document.body`), rather
+/// than at the [`Location`] that it is mounted to. The fragment still belongs to the fragment that
+/// contains the portal, so it is updated and detached along with it.
+///
+/// Content within a portal isn't part of the surrounding markup, so it is never included when
+/// rendering to a string, and is mounted rather than adopted whilst hydrating.
+pub struct Portal<B: Backend> {
+    /// A reference to the [`Backend`], used to find the target.
+    backend: B,
+
+    /// Where the content will be mounted.
+    target: PortalTarget<B>,
+
+    /// Dependencies of the content, so that it is only updated when required.
+    dependencies: Vec<usize>,
+
+    /// The content to mount within the target.
+    content: Fragment<B>,
+}
+
+impl<B: Backend> Portal<B> {
+    /// Create a new portal, which will mount `content` within `target`.
+    pub fn new(
+        backend: &B,
+        target: PortalTarget<B>,
+        dependencies: &[usize],
+        content: Fragment<B>,
+    ) -> Self {
+        Self {
+            backend: backend.clone(),
+            target,
+            dependencies: dependencies.to_vec(),
+            content,
+        }
+    }
+
+    /// Helper function to resolve the node that the content will be mounted within.
+    fn resolve_target(&self) -> Result<B::Node, Error> {
+        match &self.target {
+            PortalTarget::Node(node) => Ok(node.clone()),
+            PortalTarget::Selector(selector) => self
+                .backend
+                .query_selector(selector)
+                .ok_or_else(|| Error::MissingPortalTarget(selector.clone())),
+        }
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Portal<B> {
+    fn mount(&mut self, _location: &Location<B>) -> Result<(), Error> {
+        self.content
+            .mount(&Location::parent(&self.resolve_target()?))?;
+
+        // Any updates whilst unmounted will have been missed
+        self.content.full_update()
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        // The content was never rendered in place, so there is nothing to adopt
+        self.mount(&Location::parent(cursor.get_parent()))
+    }
+
+    fn detach(&mut self, _top_level: bool) -> Result<(), Error> {
+        // The target isn't being detached, so the content must always be removed from it
+        self.content.detach(true)
+    }
+
+    fn relocate(&mut self, _location: &Location<B>) -> Result<(), Error> {
+        // The content stays within the target
+        Ok(())
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        if changed
+            .iter()
+            .any(|changed| self.dependencies.contains(changed))
+        {
+            self.content.update(changed)?;
+        }
+
+        Ok(())
+    }
+}
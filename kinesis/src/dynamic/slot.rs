@@ -0,0 +1,68 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::fragment::{Cursor, Fragment, Location};
+use crate::Error;
+
+/// The name of the slot that content is placed in if no name is given.
+pub const DEFAULT_SLOT: &str = "default";
+
+/// Content passed into a component by its parent. The fragment is built and updated by the
+/// parent, so it can depend on the parent's fields and trigger the parent's events, however it is
+/// mounted by the nested component wherever it places the slot.
+pub type SlotContent<B> = Rc<RefCell<Fragment<B>>>;
+
+/// A position within a component's fragment where content from the parent component is mounted.
+/// If the parent didn't provide any content for the slot, nothing is mounted.
+///
+/// The content is only rendered once, so each slot should only be placed once within a component.
+pub struct Slot<B: Backend> {
+    /// The content to mount, if the parent provided any.
+    content: Option<SlotContent<B>>,
+}
+
+impl<B: Backend> Slot<B> {
+    /// Create a new slot for the provided content.
+    pub fn new(content: Option<SlotContent<B>>) -> Self {
+        Self { content }
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Slot<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        if let Some(content) = &self.content {
+            let mut content = content.borrow_mut();
+
+            content.mount(location)?;
+
+            // Any updates from the parent whilst unmounted will have been missed
+            content.full_update()?;
+        }
+
+        Ok(())
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.content
+            .iter_mut()
+            .try_for_each(|content| content.hydrate(cursor))
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.content
+            .iter_mut()
+            .try_for_each(|content| content.detach(top_level))
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.content
+            .iter_mut()
+            .try_for_each(|content| content.relocate(location))
+    }
+
+    fn update(&mut self, _changed: &[usize]) -> Result<(), Error> {
+        // The content depends on the parent's fields, so is updated by the parent
+        Ok(())
+    }
+}
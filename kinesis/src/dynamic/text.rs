@@ -0,0 +1,89 @@
+use super::Dynamic;
+use crate::backend::{Backend, NodeKind};
+use crate::fragment::{Cursor, Location};
+use crate::Error;
+
+/// A function that returns the current content of a text node.
+pub type GetTextFn = Box<dyn Fn() -> String>;
+
+/// A single text node, whose content is updated in place whenever one of its dependencies change.
+pub struct Text<B: Backend> {
+    /// The text node, which is re-used for the lifetime of this item.
+    node: B::Node,
+
+    /// Dependencies of the text, so that it is only recomputed when required.
+    dependencies: Vec<usize>,
+
+    /// A function that will return the content of the text node for the given context.
+    get_text: GetTextFn,
+
+    /// The content that is currently set on the text node.
+    content: String,
+}
+
+impl<B: Backend> Text<B> {
+    /// Create a new text node with the provided `get_text` function. Requires a reference to the
+    /// [`Backend`] in order to create the text node.
+    pub fn new(backend: &B, dependencies: &[usize], get_text: GetTextFn) -> Self {
+        Self {
+            node: backend.create_text(""),
+            dependencies: dependencies.to_vec(),
+            get_text,
+            content: String::new(),
+        }
+    }
+
+    /// Helper function to compute the content of the text node, only setting it if it has
+    /// changed.
+    fn apply(&mut self) {
+        let content = (self.get_text)();
+
+        if content != self.content {
+            B::set_text(&self.node, &content);
+            self.content = content;
+        }
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Text<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        location.mount(&self.node)?;
+
+        self.apply();
+
+        Ok(())
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.node = cursor.take_text(None)?;
+
+        // The existing content may not match, so it will only be replaced if it differs
+        self.content = match B::node_kind(&self.node) {
+            NodeKind::Text(content) => content,
+            _ => String::new(),
+        };
+
+        self.apply();
+
+        Ok(())
+    }
+
+    fn detach(&mut self, _top_level: bool) -> Result<(), Error> {
+        B::remove(&self.node)
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        location.mount(&self.node)
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        if changed
+            .iter()
+            .any(|changed| self.dependencies.contains(changed))
+        {
+            self.apply();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use super::Dynamic;
+use crate::backend::Backend;
+use crate::fragment::{Cursor, Location};
+use crate::signal::observer::Observer;
+use crate::Error;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+/// Tracks the [`crate::signal::Signal`]s read by a [`Dynamic`] whilst it is mounted or updated.
+/// When any of them change, an update of only this dynamic is scheduled with the [`Backend`],
+/// rather than waiting for the controller to be notified of a changed field.
+///
+/// Updates from the controller are passed through as normal, so field ids and signals can be used
+/// together.
+pub struct Tracked<B: Backend> {
+    /// The [`Dynamic`] being tracked, which is shared with scheduled updates.
+    dynamic: Rc<RefCell<Box<dyn Dynamic<B>>>>,
+
+    /// Subscribed to each signal read by the dynamic.
+    observer: Rc<Observer>,
+
+    /// Whether the dynamic is currently mounted, as scheduled updates must not run otherwise.
+    mounted: Rc<Cell<bool>>,
+}
+
+impl<B: Backend> Tracked<B> {
+    /// Create a new tracked dynamic.
+    pub fn new<D>(backend: &B, dynamic: D) -> Self
+    where
+        D: 'static + Dynamic<B>,
+    {
+        let dynamic = Rc::new(RefCell::new(Box::new(dynamic) as Box<dyn Dynamic<B>>));
+        let mounted = Rc::new(Cell::new(false));
+
+        // Multiple signals may change before the update runs, but only a single update is needed
+        let scheduled = Rc::new(Cell::new(false));
+
+        let observer = Rc::new_cyclic(|observer: &Weak<Observer>| {
+            let backend = backend.clone();
+            let dynamic = Rc::downgrade(&dynamic);
+            let observer = Weak::clone(observer);
+            let mounted = Rc::clone(&mounted);
+
+            Observer::new(move || {
+                if scheduled.replace(true) {
+                    return Ok(());
+                }
+
+                let dynamic = Weak::clone(&dynamic);
+                let observer = Weak::clone(&observer);
+                let mounted = Rc::clone(&mounted);
+                let scheduled = Rc::clone(&scheduled);
+
+                backend.schedule(move || {
+                    scheduled.set(false);
+
+                    match (dynamic.upgrade(), observer.upgrade()) {
+                        (Some(dynamic), Some(observer)) if mounted.get() => {
+                            observer.track(|| dynamic.borrow_mut().update(&[]))
+                        }
+                        // Dropped or detached since the update was scheduled
+                        _ => Ok(()),
+                    }
+                })
+            })
+        });
+
+        Self {
+            dynamic,
+            observer,
+            mounted,
+        }
+    }
+}
+
+impl<B: Backend> Dynamic<B> for Tracked<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.mounted.set(true);
+
+        self.observer
+            .track(|| self.dynamic.borrow_mut().mount(location))
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.mounted.set(true);
+
+        self.observer
+            .track(|| self.dynamic.borrow_mut().hydrate(cursor))
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.mounted.set(false);
+
+        self.dynamic.borrow_mut().detach(top_level)
+    }
+
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.dynamic.borrow_mut().relocate(location)
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        self.observer
+            .track(|| self.dynamic.borrow_mut().update(changed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::event_registry::EventRegistry;
+    use crate::fragment::{Fragment, FragmentBuilder};
+    use crate::html;
+    use crate::signal::{Memo, Signal};
+
+    /// The number of times that an updatable has been built.
+    type Builds = Rc<Cell<usize>>;
+
+    /// Helper function to mount an updatable within the body of `backend`, which renders the text
+    /// from `read`. Returns the mounted fragment, along with the number of times it was built.
+    fn mount<R>(backend: &MemoryBackend, read: R) -> (Fragment<MemoryBackend>, Builds)
+    where
+        R: 'static + Fn() -> String,
+    {
+        let builds = Rc::new(Cell::new(0));
+
+        let builder: FragmentBuilder<MemoryBackend> = html! {
+            {#updatable(&[], {
+                let builds = Rc::clone(&builds);
+                move || {
+                    builds.set(builds.get() + 1);
+                    html! { {read()} }
+                }
+            })}
+        };
+
+        let mut fragment = builder
+            .build(backend, &EventRegistry::new(|_, _| Ok(()), |_| Ok(())))
+            .unwrap();
+        fragment.mount(&Location::parent(&backend.body())).unwrap();
+        fragment.full_update().unwrap();
+
+        (fragment, builds)
+    }
+
+    #[test]
+    fn set_only_updates_readers() {
+        let backend = MemoryBackend::new();
+        let a = Signal::new(1);
+        let b = Signal::new(2);
+        let (_a_fragment, a_builds) = mount(&backend, {
+            let a = a.clone();
+            move || a.get().to_string()
+        });
+        let (_b_fragment, b_builds) = mount(&backend, {
+            let b = b.clone();
+            move || b.get().to_string()
+        });
+        assert_eq!((a_builds.get(), b_builds.get()), (1, 1));
+
+        a.set(3).unwrap();
+        assert_eq!(backend.body().text_content(), "12");
+
+        backend.run_scheduled().unwrap();
+        assert_eq!(backend.body().text_content(), "32");
+        assert_eq!((a_builds.get(), b_builds.get()), (2, 1));
+    }
+
+    #[test]
+    fn multiple_sets_are_coalesced() {
+        let backend = MemoryBackend::new();
+        let a = Signal::new(1);
+        let b = Signal::new(2);
+        let (_fragment, builds) = mount(&backend, {
+            let (a, b) = (a.clone(), b.clone());
+            move || format!("{}{}", a.get(), b.get())
+        });
+
+        a.set(3).unwrap();
+        a.set(4).unwrap();
+        b.set(5).unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert_eq!(backend.body().text_content(), "45");
+        assert_eq!(builds.get(), 2);
+    }
+
+    #[test]
+    fn unchanged_memo_does_not_notify() {
+        let backend = MemoryBackend::new();
+        let count = Signal::new(1);
+        let even = Memo::new({
+            let count = count.clone();
+            move || count.get() % 2 == 0
+        });
+        let (_fragment, builds) = mount(&backend, {
+            let even = even.clone();
+            move || even.get().to_string()
+        });
+
+        count.set(3).unwrap();
+        backend.run_scheduled().unwrap();
+        assert_eq!(builds.get(), 1);
+
+        // The memo must still be subscribed to the count after computing the same value
+        count.set(4).unwrap();
+        backend.run_scheduled().unwrap();
+        assert_eq!(backend.body().text_content(), "true");
+        assert_eq!(builds.get(), 2);
+    }
+
+    #[test]
+    fn detached_update_is_ignored() {
+        let backend = MemoryBackend::new();
+        let a = Signal::new(1);
+        let (mut fragment, builds) = mount(&backend, {
+            let a = a.clone();
+            move || a.get().to_string()
+        });
+
+        a.set(2).unwrap();
+        fragment.detach(true).unwrap();
+        backend.run_scheduled().unwrap();
+
+        assert!(backend.body().children().is_empty());
+        assert_eq!(builds.get(), 1);
+    }
+
+    #[test]
+    fn readers_resubscribe_after_notify() {
+        let backend = MemoryBackend::new();
+        let a = Signal::new(1);
+        let (_fragment, builds) = mount(&backend, {
+            let a = a.clone();
+            move || a.get().to_string()
+        });
+
+        for value in 2..5 {
+            a.set(value).unwrap();
+            backend.run_scheduled().unwrap();
+
+            assert_eq!(backend.body().text_content(), value.to_string());
+        }
+        assert_eq!(builds.get(), 4);
+    }
+}
@@ -1,51 +1,106 @@
-use web_sys::console;
-
-use super::Dynamic;
-use crate::fragment::Location;
+use super::{Dynamic, SlotContent};
+use crate::backend::Backend;
+use crate::controller::origin;
+use crate::fragment::{Cursor, Location};
+use crate::Error;
 
 pub type UpdateFn = dyn Fn(&[usize]) -> Option<Vec<usize>>;
 
 /// A proxy for [`Dynamic`] things, allowing for a custom `update` function to be called before the
 /// original `update` is called.
-pub struct UpdateProxy {
+pub struct UpdateProxy<B: Backend> {
     /// The [`Dynamic`] to be rendered with the proxy.
-    dynamic: Box<dyn Dynamic>,
+    dynamic: Box<dyn Dynamic<B>>,
 
     /// The update function to run before the original update function. This can include re-writing
     /// the changed dependencies, which can be passed onwards.
     proxy_update: Box<UpdateFn>,
+
+    /// Identifier of the controller being proxied, if it is one. Changes that originated from
+    /// this controller won't be passed back to it.
+    origin: Option<usize>,
+
+    /// Content provided to the slots of the controller being proxied. As it depends on the fields
+    /// of the parent, it is updated before the changes are re-written.
+    slots: Vec<SlotContent<B>>,
 }
 
-impl UpdateProxy {
+impl<B: Backend> UpdateProxy<B> {
     /// Create a new proxy with the provided dynamic and update function.
     pub fn new<D, U>(dynamic: D, proxy_update: U) -> Self
     where
-        D: 'static + Dynamic,
+        D: 'static + Dynamic<B>,
         U: 'static + Fn(&[usize]) -> Option<Vec<usize>>,
     {
         Self {
-            dynamic: Box::new(dynamic) as Box<dyn Dynamic>,
+            dynamic: Box::new(dynamic) as Box<dyn Dynamic<B>>,
             proxy_update: Box::new(proxy_update) as Box<UpdateFn>,
+            origin: None,
+            slots: Vec::new(),
         }
     }
+
+    /// Set the identifier of the controller being proxied, so that changes which originated from
+    /// it are ignored.
+    pub(crate) fn with_origin(mut self, origin: usize) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Set the content provided to the slots of the controller being proxied, so that it is
+    /// updated with the parent.
+    pub(crate) fn with_slots(mut self, slots: Vec<SlotContent<B>>) -> Self {
+        self.slots = slots;
+        self
+    }
 }
 
-impl Dynamic for UpdateProxy {
-    fn mount(&mut self, location: &Location) {
-        self.dynamic.mount(location);
+impl<B: Backend> Dynamic<B> for UpdateProxy<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.dynamic.mount(location)
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        self.dynamic.hydrate(cursor)
+    }
+
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.dynamic.detach(top_level)
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.dynamic.detach(top_level);
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        self.dynamic.relocate(location)
     }
 
-    fn update(&mut self, changed: &[usize]) {
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
+        // Slot content belongs to the parent, so receives every change
+        self.slots
+            .iter_mut()
+            .try_for_each(|slot| slot.update(changed))?;
+
+        // Don't echo changes back to where they came from
+        let changed = match self.origin {
+            Some(origin) => {
+                let echoed = origin::echoed(origin);
+                changed
+                    .iter()
+                    .filter(|changed| !echoed.contains(changed))
+                    .copied()
+                    .collect()
+            }
+            None => changed.to_vec(),
+        };
+
+        if changed.is_empty() {
+            return Ok(());
+        }
+
         // Run the update function
-        if let Some(changed) = (self.proxy_update)(changed) {
+        if let Some(changed) = (self.proxy_update)(&changed) {
             // Run the original dynamic update
-            console::log_1(&"about to borrow".into());
-            self.dynamic.update(&changed);
-            console::log_1(&"successful borrow".into());
+            self.dynamic.update(&changed)?;
         }
+
+        Ok(())
     }
 }
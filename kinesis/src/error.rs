@@ -0,0 +1,111 @@
+use std::fmt::{self, Display};
+
+use wasm_bindgen::JsValue;
+
+use crate::fragment::HydrationError;
+
+/// Errors that may occur whilst rendering.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// A node was expected to have a parent, however it isn't mounted.
+    MissingParent,
+
+    /// A location referred to a static node which doesn't exist within the fragment.
+    InvalidLocation(usize),
+
+    /// An element of the contained kind couldn't be created.
+    CreateElement(String),
+
+    /// A node couldn't be inserted into its parent.
+    InsertNode,
+
+    /// A node couldn't be removed from its parent.
+    RemoveNode,
+
+    /// The contained attribute couldn't be set or removed.
+    SetAttribute(String),
+
+    /// The contained property couldn't be set.
+    SetProperty(String),
+
+    /// A listener for the contained event type couldn't be bound.
+    BindListener(String),
+
+    /// A listener for the contained event type received an event of a different type.
+    EventType(String),
+
+    /// A handler taking a DOM event received an event of the contained type without one (such as
+    /// from [`crate::backend::MemoryBackend`]).
+    MissingEvent(String),
+
+    /// No element matched the contained selector for the target of a portal.
+    MissingPortalTarget(String),
+
+    /// A stylesheet couldn't be added to the document, as there is no `<head>`.
+    InjectStyles,
+
+    /// Work couldn't be scheduled to run in the future.
+    Schedule,
+
+    /// A [`crate::controller::ControllerRef`] was used before a controller was placed within it.
+    UnboundController,
+
+    /// The existing DOM didn't match what was expected whilst hydrating.
+    Hydration(HydrationError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingParent => write!(f, "node is not mounted within a parent"),
+            Self::InvalidLocation(location) => {
+                write!(f, "location {location} does not refer to a static node")
+            }
+            Self::CreateElement(kind) => write!(f, "unable to create element `{kind}`"),
+            Self::InsertNode => write!(f, "unable to insert node"),
+            Self::RemoveNode => write!(f, "unable to remove node"),
+            Self::SetAttribute(name) => write!(f, "unable to set attribute `{name}`"),
+            Self::SetProperty(name) => write!(f, "unable to set property `{name}`"),
+            Self::BindListener(event_type) => {
+                write!(f, "unable to bind listener for `{event_type}`")
+            }
+            Self::EventType(event_type) => {
+                write!(
+                    f,
+                    "listener for `{event_type}` received an unexpected event"
+                )
+            }
+            Self::MissingEvent(event_type) => {
+                write!(f, "handler for `{event_type}` requires a DOM event")
+            }
+            Self::MissingPortalTarget(selector) => {
+                write!(f, "unable to find portal target `{selector}`")
+            }
+            Self::InjectStyles => write!(f, "unable to inject styles into the document head"),
+            Self::Schedule => write!(f, "unable to schedule work"),
+            Self::UnboundController => write!(f, "controller reference is not bound"),
+            Self::Hydration(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Hydration(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<HydrationError> for Error {
+    fn from(error: HydrationError) -> Self {
+        Self::Hydration(error)
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
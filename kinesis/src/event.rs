@@ -0,0 +1,394 @@
+//! Typed events which can be listened to. Each kind of event (eg [`Click`]) is named after the DOM
+//! event it listens to, and determines the type of event that its handler receives (eg
+//! [`MouseEvent`]). A value for each kind is available in [`on`] by its DOM name, which is what
+//! `on:click={...}` refers to within [`crate::html`], so a misspelled event name will fail to
+//! compile.
+//!
+//! Handlers receive an [`EventPayload`], which only contains the DOM event when it was dispatched
+//! by a browser. A handler which takes a DOM event (eg [`MouseEvent`]) fails with
+//! [`Error::MissingEvent`] for an event dispatched without one, such as with
+//! [`crate::backend::MemoryBackend`], so only handlers which don't take the event can be used
+//! there.
+
+use crate::backend::Backend;
+use crate::component::Component;
+use crate::controller::Spawner;
+use crate::Error;
+
+use std::{any::Any, marker::PhantomData};
+use wasm_bindgen::JsCast;
+pub use web_sys::{
+    DragEvent, Event, FocusEvent, InputEvent, KeyboardEvent, MouseEvent, PointerEvent, SubmitEvent,
+    WheelEvent,
+};
+
+/// A kind of event that can be listened to.
+pub trait EventKind: Copy {
+    /// The name of the DOM event (eg `click`).
+    const NAME: &'static str;
+
+    /// Whether the event bubbles up through the ancestors of its target. Events which don't bubble
+    /// (eg `focus`) can't be delegated, as they never reach a listener on an ancestor.
+    const BUBBLES: bool;
+
+    /// The type of the event that is received.
+    type Event: JsCast + Into<Event>;
+}
+
+/// An [`EventKind`] with its type erased, so that different kinds can be stored together.
+#[derive(Clone, Copy)]
+pub struct EventType {
+    /// The name of the DOM event.
+    name: &'static str,
+
+    /// Whether the event bubbles.
+    bubbles: bool,
+
+    /// Check that a DOM event is of the type expected by the kind.
+    check: fn(&Event) -> bool,
+}
+
+impl EventType {
+    /// Erase the type of the provided kind.
+    pub fn of<K: EventKind>() -> Self {
+        Self {
+            name: K::NAME,
+            bubbles: K::BUBBLES,
+            check: |event| event.dyn_ref::<K::Event>().is_some(),
+        }
+    }
+
+    /// The name of the DOM event.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Whether the event bubbles.
+    pub fn bubbles(&self) -> bool {
+        self.bubbles
+    }
+
+    /// Check that the event is of the type expected by the kind, failing if it is of a different
+    /// type. Events without a DOM event only have their name checked.
+    pub(crate) fn check(&self, payload: &EventPayload) -> Result<(), Error> {
+        let matches = match payload.dom() {
+            Some(event) => (self.check)(event),
+            None => payload.event_type() == self.name,
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::EventType(self.name.to_string()))
+        }
+    }
+}
+
+/// An event received by a listener, which is passed to
+/// [`crate::component::Component::handle_event()`]. Events dispatched by a browser contain the DOM
+/// event, whilst events dispatched without one only contain their type.
+#[derive(Clone, Debug)]
+pub struct EventPayload {
+    /// The name of the event (eg `click`).
+    event_type: String,
+
+    /// The DOM event, if it was dispatched by a browser.
+    dom: Option<Event>,
+}
+
+impl EventPayload {
+    /// Create a payload for an event which was dispatched without a browser.
+    pub fn new(event_type: &str) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            dom: None,
+        }
+    }
+
+    /// Create a payload for a DOM event.
+    pub fn from_dom(event: Event) -> Self {
+        Self {
+            event_type: event.type_(),
+            dom: Some(event),
+        }
+    }
+
+    /// The name of the event.
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// The DOM event, if it was dispatched by a browser.
+    pub fn dom(&self) -> Option<&Event> {
+        self.dom.as_ref()
+    }
+}
+
+/// Context passed to [`crate::component::Component::handle_event()`] along with each event,
+/// providing the [`Spawner`] of the component handling it.
+pub struct EventContext {
+    /// The [`Spawner`] of the component, with the type of the component erased.
+    spawner: Box<dyn Any>,
+}
+
+impl EventContext {
+    /// Create a context for events handled by a component of type `C`.
+    pub fn new<C: Component + ?Sized + 'static>(spawner: Spawner<C>) -> Self {
+        Self {
+            spawner: Box::new(spawner),
+        }
+    }
+
+    /// Get the [`Spawner`] of the component handling the event, if it is a component of type `C`.
+    pub fn spawner<C: Component + ?Sized + 'static>(&self) -> Option<Spawner<C>> {
+        self.spawner.downcast_ref().cloned()
+    }
+}
+
+/// Options for how a listener is bound (`capture`, `once` and `passive`), along with modifiers
+/// which are applied to each event before it is passed to the handler. Options can be chained from
+/// [`EventOptions::new()`], which is what `on:submit|prevent_default={...}` expands to within
+/// [`crate::html`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EventOptions {
+    /// Receive the event whilst it is captured, before it reaches any descendants.
+    pub capture: bool,
+
+    /// Remove the listener after the first event is received.
+    pub once: bool,
+
+    /// Promise that the default action won't be prevented, allowing the browser to begin it (such
+    /// as scrolling) without waiting for the handler. This should not be combined with
+    /// `prevent_default`.
+    pub passive: bool,
+
+    /// Prevent the default action of the event (such as submitting a form).
+    pub prevent_default: bool,
+
+    /// Stop the event from propagating any further.
+    pub stop_propagation: bool,
+
+    /// Only call the handler if the event was dispatched to the element itself, rather than one of
+    /// its descendants.
+    pub self_only: bool,
+}
+
+impl EventOptions {
+    /// Create options with every flag disabled, matching the default options of a listener.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable [`Self::capture`].
+    pub fn capture(mut self) -> Self {
+        self.capture = true;
+        self
+    }
+
+    /// Enable [`Self::once`].
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    /// Enable [`Self::passive`].
+    pub fn passive(mut self) -> Self {
+        self.passive = true;
+        self
+    }
+
+    /// Enable [`Self::prevent_default`].
+    pub fn prevent_default(mut self) -> Self {
+        self.prevent_default = true;
+        self
+    }
+
+    /// Enable [`Self::stop_propagation`].
+    pub fn stop_propagation(mut self) -> Self {
+        self.stop_propagation = true;
+        self
+    }
+
+    /// Enable [`Self::self_only`].
+    pub fn self_only(mut self) -> Self {
+        self.self_only = true;
+        self
+    }
+
+    /// Whether a listener with these options must be bound directly to its element, as `capture`,
+    /// `once` and `passive` can't be applied through delegation.
+    pub(crate) fn requires_listener(&self) -> bool {
+        self.capture || self.once || self.passive
+    }
+
+    /// Apply the modifiers to an event, returning whether it should be passed to the handler.
+    /// `at_target` is whether the element being handled is the one the event was dispatched to.
+    pub(crate) fn apply<B: Backend>(&self, event: &B::Event, at_target: bool) -> bool {
+        if self.self_only && !at_target {
+            return false;
+        }
+
+        if self.prevent_default {
+            B::prevent_default(event);
+        }
+
+        if self.stop_propagation {
+            B::stop_propagation(event);
+        }
+
+        true
+    }
+}
+
+/// Identifies an event handler of a component, along with the type of event that it takes. This
+/// is generated by [`crate::component`] for each `#[event]` method, and a handler can only be
+/// bound to an [`EventKind`] whose event can be used as the event it takes.
+///
+/// A handler taking a DOM event can't be called for an event dispatched without one (such as with
+/// [`crate::backend::MemoryBackend`]), and will fail with [`Error::MissingEvent`] instead.
+pub struct Handler<E = Event> {
+    id: usize,
+    event: PhantomData<fn(E)>,
+}
+
+impl<E> Handler<E> {
+    /// Create a handler with the provided event id, which is passed to
+    /// [`crate::component::Component::handle_event()`].
+    pub const fn new(id: usize) -> Self {
+        Self {
+            id,
+            event: PhantomData,
+        }
+    }
+
+    /// The event id of the handler.
+    pub fn id(self) -> usize {
+        self.id
+    }
+}
+
+impl<E: JsCast> Handler<E> {
+    /// Cast a received event to the type taken by the handler, failing if it doesn't contain a
+    /// DOM event. The event must have already been checked against the kind that the handler was
+    /// bound to.
+    #[doc(hidden)]
+    pub fn cast(self, payload: EventPayload) -> Result<E, Error> {
+        let EventPayload { event_type, dom } = payload;
+
+        dom.map(JsCast::unchecked_into)
+            .ok_or(Error::MissingEvent(event_type))
+    }
+}
+
+impl<E> Clone for Handler<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for Handler<E> {}
+
+/// Define a unit struct implementing [`EventKind`] for each event, along with a value for it in
+/// [`on`]. Events which don't bubble are marked with `[no_bubble]`.
+macro_rules! event_kinds {
+    ($($kind:ident($name:ident) -> $event:ty $([$no_bubble:ident])?;)*) => {
+        $(
+            #[doc = concat!("The `", stringify!($name), "` event.")]
+            #[derive(Clone, Copy)]
+            pub struct $kind;
+
+            impl EventKind for $kind {
+                const NAME: &'static str = stringify!($name);
+                const BUBBLES: bool = event_kinds!(@bubbles $($no_bubble)?);
+                type Event = $event;
+            }
+        )*
+
+        /// Each [`EventKind`], named after the DOM event it listens to.
+        #[allow(non_upper_case_globals)]
+        pub mod on {
+            $(
+                #[doc = concat!("The `", stringify!($name), "` event.")]
+                pub const $name: super::$kind = super::$kind;
+            )*
+        }
+    };
+    (@bubbles) => { true };
+    (@bubbles no_bubble) => { false };
+}
+
+event_kinds! {
+    Click(click) -> MouseEvent;
+    DblClick(dblclick) -> MouseEvent;
+    AuxClick(auxclick) -> MouseEvent;
+    ContextMenu(contextmenu) -> MouseEvent;
+    MouseDown(mousedown) -> MouseEvent;
+    MouseUp(mouseup) -> MouseEvent;
+    MouseMove(mousemove) -> MouseEvent;
+    MouseEnter(mouseenter) -> MouseEvent [no_bubble];
+    MouseLeave(mouseleave) -> MouseEvent [no_bubble];
+    MouseOver(mouseover) -> MouseEvent;
+    MouseOut(mouseout) -> MouseEvent;
+
+    PointerDown(pointerdown) -> PointerEvent;
+    PointerUp(pointerup) -> PointerEvent;
+    PointerMove(pointermove) -> PointerEvent;
+    PointerEnter(pointerenter) -> PointerEvent [no_bubble];
+    PointerLeave(pointerleave) -> PointerEvent [no_bubble];
+    PointerOver(pointerover) -> PointerEvent;
+    PointerOut(pointerout) -> PointerEvent;
+    PointerCancel(pointercancel) -> PointerEvent;
+
+    KeyDown(keydown) -> KeyboardEvent;
+    KeyUp(keyup) -> KeyboardEvent;
+
+    Focus(focus) -> FocusEvent [no_bubble];
+    Blur(blur) -> FocusEvent [no_bubble];
+    FocusIn(focusin) -> FocusEvent;
+    FocusOut(focusout) -> FocusEvent;
+
+    // Only text fields fire an `InputEvent`, whilst `<select>`, checkboxes and radio buttons fire
+    // a plain `Event`
+    Input(input) -> Event;
+    BeforeInput(beforeinput) -> InputEvent;
+    Change(change) -> Event;
+    Select(select) -> Event;
+    Submit(submit) -> SubmitEvent;
+    Reset(reset) -> Event;
+
+    Wheel(wheel) -> WheelEvent;
+    Scroll(scroll) -> Event [no_bubble];
+
+    Drag(drag) -> DragEvent;
+    DragStart(dragstart) -> DragEvent;
+    DragEnd(dragend) -> DragEvent;
+    DragEnter(dragenter) -> DragEvent;
+    DragLeave(dragleave) -> DragEvent;
+    DragOver(dragover) -> DragEvent;
+    Drop(drop) -> DragEvent;
+
+    Load(load) -> Event [no_bubble];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::any::TypeId;
+
+    #[test]
+    fn input_takes_any_event() {
+        assert_eq!(
+            TypeId::of::<<Input as EventKind>::Event>(),
+            TypeId::of::<Event>()
+        );
+        assert_eq!(
+            EventType::of::<Input>().check(&EventPayload::new("input")),
+            Ok(())
+        );
+        assert_eq!(
+            EventType::of::<Input>().check(&EventPayload::new("change")),
+            Err(Error::EventType("input".to_string()))
+        );
+    }
+}
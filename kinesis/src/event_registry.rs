@@ -1,41 +1,307 @@
-use js_sys::Function;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-use wasm_bindgen::{prelude::Closure, JsCast};
-use web_sys::Event;
+use crate::backend::Backend;
+use crate::dynamic::SlotContent;
+use crate::event::{EventOptions, EventPayload, EventType};
+use crate::Error;
 
-pub type RegisterEventFn = Rc<dyn Fn(usize, Event)>;
+use std::{cell::RefCell, collections::HashMap, iter, rc::Rc};
 
-/// A registry of [`js_sys::Function`]s, caching created closures for a given event id.
-pub struct EventRegistry {
-    /// Cached closures.
-    closures: HashMap<usize, Function>,
+/// Attribute set on elements whose events are delegated, containing the registry id and the index
+/// of each delegated handler for the element (eg `3:0 3:1`).
+pub(crate) const DELEGATE_ATTRIBUTE: &str = "data-kinesis-on";
 
-    /// Shared reference to a callback function, which will be called when one of the closures is
+pub type RegisterEventFn = Rc<dyn Fn(usize, EventPayload) -> Result<(), Error>>;
+
+/// A function to notify the controller that fields have changed, outside of an event handler.
+pub type NotifyChangedFn = Rc<dyn Fn(&[usize]) -> Result<(), Error>>;
+
+/// A registry of [`Backend::Listener`]s, caching created listeners for a given event type, event
+/// id and options.
+pub struct EventRegistry<B: Backend> {
+    /// Cached listeners, keyed by the name of the event type, the event id and the options.
+    listeners: HashMap<(&'static str, usize, EventOptions), B::Listener>,
+
+    /// Shared reference to a callback function, which will be called when one of the listeners is
     /// called.
     register_event: RegisterEventFn,
+
+    /// Shared reference to a callback function, which will notify the controller that fields have
+    /// changed (such as from a [`crate::dynamic::Binding`]).
+    notify_changed: NotifyChangedFn,
+
+    /// Content passed into the component by its parent, which is placed with a
+    /// [`crate::dynamic::Slot`].
+    slots: HashMap<String, SlotContent<B>>,
+
+    /// The attribute used to scope the component's stylesheet, which is set on every element that
+    /// the component creates.
+    scope: Option<String>,
+
+    /// State for delegating events to the root, if enabled.
+    delegation: Option<Delegation<B>>,
+}
+
+/// Events which are handled by a single listener on the root for each event type, rather than a
+/// listener on every element. Each element is tagged with [`DELEGATE_ATTRIBUTE`], which the root
+/// listener finds by walking up from the target of the event.
+struct Delegation<B: Backend> {
+    /// Identifies the registry within [`DELEGATE_ATTRIBUTE`], so that an element of a nested
+    /// component is never handled by its parent (and vice versa).
+    id: usize,
+
+    /// Each delegated handler, shared with the root listeners. The index of a handler is what an
+    /// element is tagged with.
+    handlers: Rc<RefCell<Vec<(EventType, usize, EventOptions)>>>,
+
+    /// The index of each handler, so that each is only added once.
+    indexes: HashMap<(&'static str, usize, EventOptions), usize>,
+
+    /// The node that the root listeners are bound to, once the component has been mounted.
+    root: Option<B::Node>,
+
+    /// Event types which a root listener has been bound for.
+    listening: Vec<&'static str>,
 }
 
-impl EventRegistry {
+impl<B: Backend> EventRegistry<B> {
     /// Create a new registry, returning a shared reference.
-    pub fn new<F>(register_event: F) -> Rc<RefCell<Self>>
+    pub fn new<F, N>(register_event: F, notify_changed: N) -> Rc<RefCell<Self>>
     where
-        F: 'static + Fn(usize, Event),
+        F: 'static + Fn(usize, EventPayload) -> Result<(), Error>,
+        N: 'static + Fn(&[usize]) -> Result<(), Error>,
     {
         Rc::new(RefCell::new(Self {
-            closures: HashMap::new(),
+            listeners: HashMap::new(),
             register_event: Rc::new(register_event),
+            notify_changed: Rc::new(notify_changed),
+            slots: HashMap::new(),
+            scope: None,
+            delegation: None,
         }))
     }
 
-    /// Get or create a closure for the provided event id.
-    pub fn get(&mut self, event_id: usize) -> &Function {
-        self.closures.entry(event_id).or_insert_with(|| {
-            let register_event = Rc::clone(&self.register_event);
-            Closure::<dyn Fn(Event)>::new(move |event| {
-                register_event(event_id, event);
+    /// Get a reference to the callback which notifies the controller that fields have changed.
+    pub fn get_notify_changed(&self) -> NotifyChangedFn {
+        Rc::clone(&self.notify_changed)
+    }
+
+    /// Get or create a listener for the provided event type, event id and options. The listener
+    /// checks that each event is of the type expected by `event_type`, and applies the modifiers
+    /// of `options`, before it is passed to the component.
+    pub fn get(
+        &mut self,
+        event_type: EventType,
+        event_id: usize,
+        options: EventOptions,
+    ) -> &B::Listener {
+        self.listeners
+            .entry((event_type.name(), event_id, options))
+            .or_insert_with(|| {
+                let register_event = Rc::clone(&self.register_event);
+                B::create_listener(move |event| {
+                    let payload = B::event_payload(&event);
+                    event_type.check(&payload)?;
+
+                    if !options.apply::<B>(&event, B::event_at_target(&event)) {
+                        return Ok(());
+                    }
+
+                    register_event(event_id, payload)
+                })
+            })
+    }
+
+    /// Bind listeners to `node` for each of the events, along with the id of the event handler to
+    /// call and the options to listen with. If events are delegated, the node is instead tagged
+    /// with [`DELEGATE_ATTRIBUTE`] (other than for events which require their own listener, see
+    /// [`Self::delegate()`]).
+    pub fn listen(
+        &mut self,
+        node: &B::Node,
+        events: &[(EventType, usize, EventOptions)],
+    ) -> Result<(), Error> {
+        let mut delegated = Vec::new();
+
+        for (event_type, event_id, options) in events {
+            match self.delegation.as_mut() {
+                Some(delegation) if event_type.bubbles() && !options.requires_listener() => {
+                    let index = delegation.add(*event_type, *event_id, *options);
+                    delegated.push(format!("{}:{index}", delegation.id));
+
+                    delegation.listen_root(event_type.name(), &self.register_event)?;
+                }
+                _ => B::add_listener(
+                    node,
+                    event_type.name(),
+                    self.get(*event_type, *event_id, *options),
+                    options,
+                )?,
+            }
+        }
+
+        if !delegated.is_empty() {
+            B::set_attribute(node, DELEGATE_ATTRIBUTE, &delegated.join(" "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delegate events to a single listener for each event type on the root, rather than binding
+    /// a listener to every element. `id` must be unique to the registry. Listeners for events
+    /// which don't bubble (as they never reach the root), or with the `capture`, `once` or
+    /// `passive` options, are still bound to their element. Events from elements which aren't
+    /// within the root (such as through a [`crate::dynamic::Portal`]) won't be received.
+    pub fn delegate(&mut self, id: usize) {
+        self.delegation = Some(Delegation {
+            id,
+            handlers: Rc::new(RefCell::new(Vec::new())),
+            indexes: HashMap::new(),
+            root: None,
+            listening: Vec::new(),
+        });
+    }
+
+    /// Set the root that delegated events are listened to on, binding a listener for each event
+    /// type that has been delegated so far. Only the first root is used, so this should be the
+    /// parent that the component is first mounted within.
+    pub fn set_root(&mut self, root: &B::Node) -> Result<(), Error> {
+        let Some(delegation) = self.delegation.as_mut() else {
+            return Ok(());
+        };
+
+        if delegation.root.is_some() {
+            return Ok(());
+        }
+
+        delegation.root = Some(root.clone());
+
+        let event_types = delegation
+            .indexes
+            .keys()
+            .map(|(event_type, _, _)| *event_type)
+            .collect::<Vec<_>>();
+        event_types
+            .into_iter()
+            .try_for_each(|event_type| delegation.listen_root(event_type, &self.register_event))
+    }
+
+    /// Set the content for the slot with the provided name.
+    pub fn set_slot(&mut self, name: &str, content: SlotContent<B>) {
+        self.slots.insert(name.to_string(), content);
+    }
+
+    /// Get the content for the slot with the provided name, if the parent provided any.
+    pub fn get_slot(&self, name: &str) -> Option<SlotContent<B>> {
+        self.slots.get(name).cloned()
+    }
+
+    /// Set the attribute used to scope the component's stylesheet.
+    pub fn set_scope(&mut self, scope: &str) {
+        self.scope = Some(scope.to_string());
+    }
+
+    /// Get the attribute used to scope the component's stylesheet, if it has one.
+    pub fn get_scope(&self) -> Option<String> {
+        self.scope.clone()
+    }
+}
+
+impl<B: Backend> Delegation<B> {
+    /// Get the index of a handler, adding it if it hasn't been delegated before.
+    fn add(&mut self, event_type: EventType, event_id: usize, options: EventOptions) -> usize {
+        *self
+            .indexes
+            .entry((event_type.name(), event_id, options))
+            .or_insert_with(|| {
+                let mut handlers = self.handlers.borrow_mut();
+                handlers.push((event_type, event_id, options));
+
+                handlers.len() - 1
             })
-            .into_js_value()
-            .unchecked_into()
-        })
     }
+
+    /// Bind a listener to the root for the event type, if there is a root and it isn't already
+    /// being listened to.
+    fn listen_root(
+        &mut self,
+        event_type: &'static str,
+        register_event: &RegisterEventFn,
+    ) -> Result<(), Error> {
+        let Some(root) = &self.root else {
+            return Ok(());
+        };
+
+        if self.listening.contains(&event_type) {
+            return Ok(());
+        }
+
+        let listener = B::create_listener({
+            let id = self.id;
+            let root = root.clone();
+            let handlers = Rc::clone(&self.handlers);
+            let register_event = Rc::clone(register_event);
+
+            move |event| dispatch::<B>(id, &root, event_type, &handlers, &register_event, event)
+        });
+
+        B::add_listener(root, event_type, &listener, &EventOptions::default())?;
+        self.listening.push(event_type);
+
+        Ok(())
+    }
+}
+
+/// Helper function to dispatch an event received by a root listener to each element it passed
+/// through, starting from its target and stopping at the root. Stops after the element which had
+/// a handler with `stop_propagation`.
+fn dispatch<B: Backend>(
+    id: usize,
+    root: &B::Node,
+    event_type: &str,
+    handlers: &RefCell<Vec<(EventType, usize, EventOptions)>>,
+    register_event: &RegisterEventFn,
+    event: B::Event,
+) -> Result<(), Error> {
+    let prefix = format!("{id}:");
+    let payload = B::event_payload(&event);
+
+    // Elements outside of the root (such as those of a parent component) are never handled
+    let nodes =
+        iter::successors(B::event_target(&event), B::parent).take_while(|node| node != root);
+
+    for (depth, node) in nodes.enumerate() {
+        let Some(tagged) = B::get_attribute(&node, DELEGATE_ATTRIBUTE) else {
+            continue;
+        };
+
+        let mut stopped = false;
+
+        for index in tagged
+            .split(' ')
+            .filter_map(|handler| handler.strip_prefix(&prefix)?.parse::<usize>().ok())
+        {
+            // Handlers may be added whilst the event is handled, so don't hold the borrow
+            let Some((handler_type, event_id, options)) = handlers.borrow().get(index).copied()
+            else {
+                continue;
+            };
+
+            if handler_type.name() != event_type {
+                continue;
+            }
+
+            handler_type.check(&payload)?;
+            if !options.apply::<B>(&event, depth == 0) {
+                continue;
+            }
+
+            register_event(event_id, payload.clone())?;
+            stopped |= options.stop_propagation;
+        }
+
+        if stopped {
+            break;
+        }
+    }
+
+    Ok(())
 }
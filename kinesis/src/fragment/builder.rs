@@ -1,10 +1,15 @@
 use super::{EventRegistry, Fragment, Node};
-use crate::component::{Component, ComponentWrapper};
-use crate::dynamic::{Dynamic, GetIterFn, Iterator, UpdateFn, UpdateProxy};
-use crate::Controller;
+use crate::backend::PropertyValue;
+use crate::backend::{Backend, WebBackend};
+use crate::component::{indexes, Component, ComponentWrapper, Field, Props};
+use crate::dynamic::{
+    Attribute, Await, Binding, BindingKind, Dynamic, GetAttributeFn, GetAwaitFn, GetBindingFn,
+    GetIterFn, GetKeyedIterFn, GetPendingFn, GetTextFn, Iterator, KeyedIterator, Portal,
+    PortalTarget, SetBindingFn, Slot, Text, Tracked, UpdateFn, UpdateProxy, DEFAULT_SLOT,
+};
+use crate::{Controller, Error};
 
-use std::{cell::RefCell, iter, rc::Rc};
-use web_sys::Document;
+use std::{cell::RefCell, future::Future, hash::Hash, iter, marker::PhantomData, pin::Pin, rc::Rc};
 
 /// Builder for a [`super::Node`].
 pub struct NodeBuilder {
@@ -13,7 +18,7 @@ pub struct NodeBuilder {
 }
 
 /// Wrapper types for builders, containing common information between the builders.
-pub struct Builder {
+pub struct Builder<B: Backend> {
     /// A list of dependencies that the built result will rely on.
     dependencies: Vec<usize>,
 
@@ -22,197 +27,752 @@ pub struct Builder {
     location: Option<usize>,
 
     /// The builder with specific fields.
-    builder: Box<dyn DynamicBuilder>,
+    builder: Box<dyn DynamicBuilder<B>>,
 }
 
-trait DynamicBuilder {
+trait DynamicBuilder<B: Backend> {
     fn build(
         self: Box<Self>,
-        document: &Document,
-        event_registry: &Rc<RefCell<EventRegistry>>,
-    ) -> Box<dyn Dynamic>;
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error>;
 }
 
 /// Builder for a [`Iterator`].
-pub struct IteratorBuilder {
+pub struct IteratorBuilder<B: Backend> {
     /// A function that will return an iterator of [`FragmentBuilder`]s.
-    get_items: GetIterFn,
+    get_items: GetIterFn<B>,
 }
 
-impl DynamicBuilder for IteratorBuilder {
+impl<B: Backend> DynamicBuilder<B> for IteratorBuilder<B> {
     fn build(
         self: Box<Self>,
-        document: &Document,
-        event_registry: &Rc<RefCell<EventRegistry>>,
-    ) -> Box<dyn Dynamic> {
-        Box::new(Iterator::new(document, self.get_items, event_registry))
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        // Signals read whilst producing the items will trigger the iterator to update
+        Ok(Box::new(Tracked::new(
+            backend,
+            Iterator::new(backend, self.get_items, event_registry),
+        )))
     }
 }
 
-pub struct ControllerBuilder {
-    component: ComponentWrapper<dyn Component>,
-    map_changed: Box<UpdateFn>,
+/// Builder for a [`KeyedIterator`].
+pub struct KeyedIteratorBuilder<K, B: Backend> {
+    /// A function that will return an iterator of keys and [`FragmentBuilder`]s.
+    get_items: GetKeyedIterFn<K, B>,
+}
+
+impl<K, B> DynamicBuilder<B> for KeyedIteratorBuilder<K, B>
+where
+    K: 'static + Eq + Hash + Clone,
+    B: Backend,
+{
+    fn build(
+        self: Box<Self>,
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Tracked::new(
+            backend,
+            KeyedIterator::new(backend, self.get_items, event_registry),
+        )))
+    }
+}
+
+/// Builder for an [`Await`].
+pub struct AwaitBuilder<B: Backend> {
+    dependencies: Vec<usize>,
+
+    /// A function that will start the future, resolving to the [`FragmentBuilder`] to show.
+    get_future: GetAwaitFn<B>,
+
+    /// A function that will return the [`FragmentBuilder`] to show whilst the future is pending.
+    get_pending: GetPendingFn<B>,
+}
+
+impl<B: Backend> DynamicBuilder<B> for AwaitBuilder<B> {
+    fn build(
+        self: Box<Self>,
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Await::new(
+            backend,
+            &self.dependencies,
+            self.get_future,
+            self.get_pending,
+            event_registry,
+        )))
+    }
+}
+
+/// Builder for an [`Attribute`].
+pub struct AttributeBuilder {
+    name: String,
+    dependencies: Vec<usize>,
+    get_value: GetAttributeFn,
+}
+
+impl<B: Backend> DynamicBuilder<B> for AttributeBuilder {
+    fn build(
+        self: Box<Self>,
+        _backend: &B,
+        _event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Attribute::new(
+            &self.name,
+            &self.dependencies,
+            self.get_value,
+        )))
+    }
+}
+
+/// Builder for a [`Binding`].
+pub struct BindingBuilder {
+    kind: BindingKind,
+    id: usize,
+    get_value: GetBindingFn,
+    set_value: SetBindingFn,
+}
+
+impl<B: Backend> DynamicBuilder<B> for BindingBuilder {
+    fn build(
+        self: Box<Self>,
+        _backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Binding::<B>::new(
+            self.kind,
+            self.id,
+            self.get_value,
+            self.set_value,
+            event_registry.borrow().get_notify_changed(),
+        )))
+    }
+}
+
+/// Builder for a [`Portal`].
+pub struct PortalBuilder<B: Backend> {
+    target: PortalTarget<B>,
+    dependencies: Vec<usize>,
+    content: FragmentBuilder<B>,
+}
+
+impl<B: Backend> DynamicBuilder<B> for PortalBuilder<B> {
+    fn build(
+        self: Box<Self>,
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Portal::new(
+            backend,
+            self.target,
+            &self.dependencies,
+            self.content.build(backend, event_registry)?,
+        )))
+    }
+}
+
+/// Builder for a [`Slot`].
+pub struct SlotBuilder {
+    name: String,
+}
+
+impl<B: Backend> DynamicBuilder<B> for SlotBuilder {
+    fn build(
+        self: Box<Self>,
+        _backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Slot::new(
+            event_registry.borrow().get_slot(&self.name),
+        )))
+    }
+}
+
+/// Builder for a [`Text`].
+pub struct TextBuilder {
+    dependencies: Vec<usize>,
+    get_text: GetTextFn,
+}
+
+impl<B: Backend> DynamicBuilder<B> for TextBuilder {
+    fn build(
+        self: Box<Self>,
+        backend: &B,
+        _event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        Ok(Box::new(Text::new(
+            backend,
+            &self.dependencies,
+            self.get_text,
+        )))
+    }
+}
+
+/// Function to produce the props for a nested component from the state of its parent.
+type GetPropsFn<P> = Box<dyn Fn() -> P>;
+
+/// Builder for a nested [`Controller`].
+pub struct ControllerBuilder<C: Component + ?Sized, B: Backend> {
+    component: ComponentWrapper<C, B>,
+    get_props: GetPropsFn<C::Props>,
     bound_update: Option<Box<UpdateFn>>,
 }
 
-impl DynamicBuilder for ControllerBuilder {
+impl<C, B> DynamicBuilder<B> for ControllerBuilder<C, B>
+where
+    C: Component + ?Sized + 'static,
+    B: Backend,
+{
     fn build(
         self: Box<Self>,
-        document: &Document,
-        _event_registry: &Rc<RefCell<EventRegistry>>,
-    ) -> Box<dyn Dynamic> {
-        Box::new(UpdateProxy::new(
-            Controller::new(document, self.component, self.bound_update),
-            self.map_changed,
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Box<dyn Dynamic<B>>, Error> {
+        let Self {
+            component,
+            get_props,
+            bound_update,
+        } = *self;
+
+        // Apply the initial props, which doesn't require an update as the nested component is yet
+        // to be mounted
+        let props = get_props();
+        let changed = props.changed(None);
+        if !changed.is_empty() {
+            component
+                .component
+                .borrow_mut()
+                .handle_props(&props, &changed);
+        }
+
+        // Only notify the nested component of changes if the props actually changed
+        let map_changed = {
+            let component = component.clone_component();
+            let previous = RefCell::new(props);
+
+            move |_: &[usize]| {
+                let props = get_props();
+                let changed = props.changed(Some(&previous.borrow()));
+
+                if changed.is_empty() {
+                    return None;
+                }
+
+                let changed = component.borrow_mut().handle_props(&props, &changed);
+                *previous.borrow_mut() = props;
+
+                changed.map(|changed| indexes(&changed))
+            }
+        };
+
+        let (controller, slots) =
+            Controller::new_nested(backend, component, bound_update, event_registry)?;
+        let id = controller.borrow().id();
+
+        Ok(Box::new(
+            UpdateProxy::new(controller, map_changed)
+                .with_origin(id)
+                .with_slots(slots),
         ))
     }
 }
 
-/// Used to build and represent a [`Fragment`] that does not yet have access to the [Document].
+/// Used to build and represent a [`Fragment`] that does not yet have access to the [`Backend`].
 /// Contains a collection of each of the possible builders.
-pub struct FragmentBuilder {
+///
+/// Dependencies are described with `F`, which is typically the [`Component::Field`] of the
+/// component that the fragment belongs to, so that depending on a field which doesn't exist will
+/// fail to compile.
+pub struct FragmentBuilder<B: Backend = WebBackend, F: Field = usize> {
     /// Static nodes to be rendered within this fragment.
     nodes: Vec<NodeBuilder>,
 
-    dynamic: Vec<Builder>,
+    dynamic: Vec<Builder<B>>,
+
+    /// Whether each item added to the builder was static (`true`) or dynamic (`false`), in the
+    /// order they were added.
+    order: Vec<bool>,
+
+    /// Attributes to set on static elements, along with the ID of the element. These are only
+    /// applied when built, so that an invalid ID can be reported as an error.
+    attributes: Vec<(usize, String, String)>,
+
+    /// Dependencies are stored as the index of each field, so the type of the field is only
+    /// required whilst adding items.
+    field: PhantomData<F>,
 }
 
-impl FragmentBuilder {
+impl<B: Backend, F: Field> FragmentBuilder<B, F> {
     /// Create a new, empty instance.
     pub fn new() -> Self {
         Self {
             nodes: Vec::new(),
             dynamic: Vec::new(),
+            order: Vec::new(),
+            attributes: Vec::new(),
+            field: PhantomData,
+        }
+    }
+
+    /// Helper function to discard the type of the fields, once the dependencies have been checked
+    /// against it.
+    pub(crate) fn erase_fields(self) -> FragmentBuilder<B> {
+        FragmentBuilder {
+            nodes: self.nodes,
+            dynamic: self.dynamic,
+            order: self.order,
+            attributes: self.attributes,
+            field: PhantomData,
         }
     }
 
     /// Add a [`NodeBuilder`] to the builder.
     pub fn with_node(mut self, node: Node, location: Option<usize>) -> Self {
         self.nodes.push(NodeBuilder { node, location });
+        self.order.push(true);
         self
     }
 
-    /// Add a [`IteratorBuilder`] to the builder.
-    pub fn with_iter<F>(
-        mut self,
-        dependencies: &[usize],
+    /// Add a [`IteratorBuilder`] to the builder. Any [`crate::signal::Signal`]s read whilst
+    /// producing the items are tracked, and the items will be rebuilt whenever one of them
+    /// changes, in addition to whenever a dependency changes.
+    pub fn with_iter<I>(self, dependencies: &[F], location: Option<usize>, get_items: I) -> Self
+    where
+        I: 'static + Fn() -> Box<dyn std::iter::Iterator<Item = FragmentBuilder<B, F>>>,
+    {
+        self.with_builder(
+            dependencies,
+            location,
+            IteratorBuilder {
+                get_items: Box::new(move || {
+                    Box::new(get_items().map(FragmentBuilder::erase_fields))
+                        as Box<dyn std::iter::Iterator<Item = _>>
+                }) as GetIterFn<B>,
+            },
+        )
+    }
+
+    /// Add a [`KeyedIteratorBuilder`] to the builder. Each item is identified by a key, so that
+    /// when the dependencies change, only items with new keys are built and only items with
+    /// removed keys are detached. Items with a key that was already rendered will re-use their
+    /// existing [`Fragment`] (and are moved if re-ordered), meaning their [`FragmentBuilder`] is
+    /// discarded. Signals are tracked in the same way as [`Self::with_iter()`].
+    pub fn with_keyed_iter<K, I>(
+        self,
+        dependencies: &[F],
         location: Option<usize>,
-        get_items: F,
+        get_items: I,
     ) -> Self
     where
-        F: 'static + Fn() -> Box<dyn std::iter::Iterator<Item = FragmentBuilder>>,
+        K: 'static + Eq + Hash + Clone,
+        I: 'static + Fn() -> Box<dyn std::iter::Iterator<Item = (K, FragmentBuilder<B, F>)>>,
     {
-        self.dynamic.push(Builder {
-            dependencies: dependencies.to_vec(),
+        self.with_builder(
+            dependencies,
             location,
-            builder: Box::new(IteratorBuilder {
-                get_items: Box::new(get_items) as GetIterFn,
-            }),
-        });
-        self
+            KeyedIteratorBuilder {
+                get_items: Box::new(move || {
+                    Box::new(get_items().map(|(key, builder)| (key, builder.erase_fields())))
+                        as Box<dyn std::iter::Iterator<Item = _>>
+                }) as GetKeyedIterFn<K, B>,
+            },
+        )
     }
 
-    pub fn with_component<C, F, B>(
-        mut self,
-        dependencies: &[usize],
+    /// Add a [`ControllerBuilder`] to the builder, nesting `component` within this fragment.
+    /// Whenever a dependency changes, `get_props` is used to produce the props for the nested
+    /// component from the state of this component, and the nested component is only notified of
+    /// the fields of the props that changed (see [`Component::handle_props()`]).
+    ///
+    /// `bound_update` is called with the changed fields of the nested component, and returns the
+    /// fields of this component that changed as a result.
+    pub fn with_component<C, P, U>(
+        self,
+        dependencies: &[F],
         location: Option<usize>,
-        component: ComponentWrapper<C>,
-        update: F,
-        bound_update: Option<B>,
+        component: ComponentWrapper<C, B>,
+        get_props: P,
+        bound_update: Option<U>,
     ) -> Self
     where
         C: Component + 'static,
-        F: 'static + Fn(&[usize]) -> Option<Vec<usize>>,
-        B: 'static + Fn(&[usize]) -> Option<Vec<usize>>,
+        P: 'static + Fn() -> C::Props,
+        U: 'static + Fn(&[C::Field]) -> Option<Vec<F>>,
+    {
+        let bound_update = bound_update.map(|bound_update| {
+            Box::new(move |changed: &[usize]| {
+                let changed = changed
+                    .iter()
+                    .filter_map(|changed| C::Field::from_index(*changed))
+                    .collect::<Vec<_>>();
+
+                bound_update(&changed).map(|changed| indexes(&changed))
+            }) as Box<UpdateFn>
+        });
+
+        self.with_builder(
+            dependencies,
+            location,
+            ControllerBuilder {
+                component,
+                get_props: Box::new(get_props) as GetPropsFn<C::Props>,
+                bound_update,
+            },
+        )
+    }
+
+    /// Add a builder for a [`Dynamic`] to the builder.
+    fn with_builder<D>(mut self, dependencies: &[F], location: Option<usize>, builder: D) -> Self
+    where
+        D: 'static + DynamicBuilder<B>,
     {
         self.dynamic.push(Builder {
-            dependencies: dependencies.to_vec(),
+            dependencies: indexes(dependencies),
             location,
-            builder: Box::new(ControllerBuilder {
-                component: component.into_any(),
-                map_changed: Box::new(update),
-                bound_update: bound_update
-                    .map(|bound_update| Box::new(bound_update) as Box<UpdateFn>),
-            }),
+            builder: Box::new(builder),
         });
-
+        self.order.push(false);
         self
     }
 
     /// Helper function to create an 'updatable' fragment, meaning a fragment that is re-rendered
     /// whenever a dependency changes. This creates an [`iter::Iterator`], as with
     /// [`Self::with_iter()`].
-    pub fn with_updatable<F>(
+    pub fn with_updatable<G>(
         self,
-        dependencies: &[usize],
+        dependencies: &[F],
         location: Option<usize>,
-        get_fragment: F,
+        get_fragment: G,
     ) -> Self
     where
-        F: 'static + Fn() -> FragmentBuilder,
+        G: 'static + Fn() -> FragmentBuilder<B, F>,
     {
         self.with_iter(dependencies, location, move || {
             Box::new(iter::once(get_fragment()))
         })
     }
 
+    /// Add a [`TextBuilder`] to the builder, creating a single text node which has its content
+    /// updated in place whenever a dependency changes. This should be preferred over
+    /// [`Self::with_updatable()`] for text, as it will not rebuild any nodes.
+    pub fn with_dynamic_text<G>(
+        self,
+        dependencies: &[F],
+        location: Option<usize>,
+        get_text: G,
+    ) -> Self
+    where
+        G: 'static + Fn() -> String,
+    {
+        self.with_builder(
+            dependencies,
+            location,
+            TextBuilder {
+                dependencies: indexes(dependencies),
+                get_text: Box::new(get_text) as GetTextFn,
+            },
+        )
+    }
+
     /// Helper function to create a 'conditional' fragment, meaning a fragment that may be
     /// re-rendered whenever a dependency changes. Will handle the mounting/unmounting of the
     /// component depending on some condition that is passed in. This utilises [`bool::then()`] to
     /// create an [`Option`] containing the built fragment.
-    pub fn with_conditional<F, B>(
+    pub fn with_conditional<C, G>(
         self,
-        dependencies: &[usize],
+        dependencies: &[F],
         location: Option<usize>,
-        check_condition: F,
-        build_fragment: B,
+        check_condition: C,
+        build_fragment: G,
     ) -> Self
     where
-        F: 'static + Fn() -> bool,
-        B: 'static + Fn() -> FragmentBuilder,
+        C: 'static + Fn() -> bool,
+        G: 'static + Fn() -> FragmentBuilder<B, F>,
     {
         self.with_iter(dependencies, location, move || {
             Box::new(check_condition().then(&build_fragment).into_iter())
         })
     }
 
+    /// Add an [`AwaitBuilder`] to the builder, which shows the fragment from `pending` whilst the
+    /// future from `get_future` runs. Once it completes, the fragment is swapped for the one from
+    /// `resolved`, or nothing is shown if the future failed (see [`Self::with_await_or_else()`] to
+    /// show something for an error). The future is re-run whenever a dependency changes, and the
+    /// result of a previous run is discarded if it hasn't completed yet.
+    pub fn with_await<T, E, G, Fut, P, R>(
+        self,
+        dependencies: &[F],
+        location: Option<usize>,
+        get_future: G,
+        pending: P,
+        resolved: R,
+    ) -> Self
+    where
+        T: 'static,
+        E: 'static,
+        G: 'static + Fn() -> Fut,
+        Fut: 'static + Future<Output = Result<T, E>>,
+        P: 'static + Fn() -> FragmentBuilder<B, F>,
+        R: 'static + Fn(T) -> FragmentBuilder<B, F>,
+    {
+        self.with_await_or_else(
+            dependencies,
+            location,
+            get_future,
+            pending,
+            resolved,
+            |_| FragmentBuilder::new(),
+        )
+    }
+
+    /// Add an [`AwaitBuilder`] to the builder, as with [`Self::with_await()`], which shows the
+    /// fragment from `error` if the future fails.
+    pub fn with_await_or_else<T, E, G, Fut, P, R, X>(
+        self,
+        dependencies: &[F],
+        location: Option<usize>,
+        get_future: G,
+        pending: P,
+        resolved: R,
+        error: X,
+    ) -> Self
+    where
+        T: 'static,
+        E: 'static,
+        G: 'static + Fn() -> Fut,
+        Fut: 'static + Future<Output = Result<T, E>>,
+        P: 'static + Fn() -> FragmentBuilder<B, F>,
+        R: 'static + Fn(T) -> FragmentBuilder<B, F>,
+        X: 'static + Fn(E) -> FragmentBuilder<B, F>,
+    {
+        let resolved = Rc::new(resolved);
+        let error = Rc::new(error);
+
+        self.with_builder(
+            dependencies,
+            location,
+            AwaitBuilder {
+                dependencies: indexes(dependencies),
+                get_future: Rc::new(move || {
+                    let future = get_future();
+                    let resolved = Rc::clone(&resolved);
+                    let error = Rc::clone(&error);
+
+                    Box::pin(async move {
+                        match future.await {
+                            Ok(value) => resolved(value).erase_fields(),
+                            Err(e) => error(e).erase_fields(),
+                        }
+                    }) as Pin<Box<dyn Future<Output = _>>>
+                }) as GetAwaitFn<B>,
+                get_pending: Box::new(move || pending().erase_fields()) as GetPendingFn<B>,
+            },
+        )
+    }
+
     /// Helper function to add an element [`Node`].
     pub fn with_element(self, kind: impl AsRef<str>, location: Option<usize>) -> Self {
         self.with_node(Node::element(kind), location)
     }
 
+    /// Helper function to set an attribute on a static element [`Node`] that has already been
+    /// added to the builder. `location` is the ID of the element, in the same way that it is used
+    /// for the `location` of other items.
+    pub fn with_attribute(
+        mut self,
+        location: usize,
+        name: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Self {
+        self.attributes.push((
+            location,
+            name.as_ref().to_string(),
+            value.as_ref().to_string(),
+        ));
+        self
+    }
+
+    /// Add an [`AttributeBuilder`] to the builder, which will set an attribute on a static
+    /// element [`Node`] whenever a dependency changes. `location` is the ID of the element. If
+    /// `get_value` returns [`None`], the attribute will be removed from the element.
+    pub fn with_dynamic_attribute<G>(
+        self,
+        dependencies: &[F],
+        location: usize,
+        name: impl AsRef<str>,
+        get_value: G,
+    ) -> Self
+    where
+        G: 'static + Fn() -> Option<String>,
+    {
+        self.with_builder(
+            dependencies,
+            Some(location),
+            AttributeBuilder {
+                name: name.as_ref().to_string(),
+                dependencies: indexes(dependencies),
+                get_value: Box::new(get_value) as GetAttributeFn,
+            },
+        )
+    }
+
+    /// Add a [`BindingBuilder`] to the builder, which keeps the `value` of a static `<input>`,
+    /// `<textarea>` or `<select>` in sync with the field `id`. `location` is the ID of the
+    /// element. The value is set from `get_value` whenever the field changes, and when the user
+    /// changes the value it is passed to `set_value` before notifying the controller that the
+    /// field has changed.
+    pub fn with_value_binding<G, S>(
+        self,
+        id: F,
+        location: usize,
+        get_value: G,
+        set_value: S,
+    ) -> Self
+    where
+        G: 'static + Fn() -> String,
+        S: 'static + Fn(String),
+    {
+        self.with_builder(
+            &[id],
+            Some(location),
+            BindingBuilder {
+                kind: BindingKind::Value,
+                id: id.index(),
+                get_value: Box::new(move || PropertyValue::String(get_value())),
+                set_value: Rc::new(move |value| {
+                    if let PropertyValue::String(value) = value {
+                        set_value(value);
+                    }
+                }),
+            },
+        )
+    }
+
+    /// Add a [`BindingBuilder`] to the builder, which keeps the `checked` state of a static
+    /// checkbox or radio button in sync with the field `id`, in the same way as
+    /// [`Self::with_value_binding()`].
+    pub fn with_checked_binding<G, S>(
+        self,
+        id: F,
+        location: usize,
+        get_checked: G,
+        set_checked: S,
+    ) -> Self
+    where
+        G: 'static + Fn() -> bool,
+        S: 'static + Fn(bool),
+    {
+        self.with_builder(
+            &[id],
+            Some(location),
+            BindingBuilder {
+                kind: BindingKind::Checked,
+                id: id.index(),
+                get_value: Box::new(move || PropertyValue::Bool(get_checked())),
+                set_value: Rc::new(move |value| {
+                    if let PropertyValue::Bool(checked) = value {
+                        set_checked(checked);
+                    }
+                }),
+            },
+        )
+    }
+
+    /// Add a [`PortalBuilder`] to the builder, which will mount `content` within `target` (either
+    /// a [`Backend::Node`], or a selector such as `"body"`) rather than at `location`. The content
+    /// is still updated and detached along with this fragment, and is updated whenever a
+    /// dependency changes.
+    pub fn with_portal(
+        self,
+        dependencies: &[F],
+        location: Option<usize>,
+        target: impl Into<PortalTarget<B>>,
+        content: FragmentBuilder<B, F>,
+    ) -> Self {
+        self.with_builder(
+            dependencies,
+            location,
+            PortalBuilder {
+                target: target.into(),
+                dependencies: indexes(dependencies),
+                content: content.erase_fields(),
+            },
+        )
+    }
+
+    /// Add a [`SlotBuilder`] to the builder, which will mount the content that the parent
+    /// component provided for the slot with the provided name (see
+    /// [`ComponentWrapper::with_slot()`]). The content is updated by the parent, so the slot has no
+    /// dependencies.
+    pub fn with_slot(self, name: impl AsRef<str>, location: Option<usize>) -> Self {
+        self.with_builder(
+            &[],
+            location,
+            SlotBuilder {
+                name: name.as_ref().to_string(),
+            },
+        )
+    }
+
+    /// Add a [`SlotBuilder`] for the default slot, in the same way as [`Self::with_slot()`].
+    pub fn with_default_slot(self, location: Option<usize>) -> Self {
+        self.with_slot(DEFAULT_SLOT, location)
+    }
+
     /// Helper function to add a text [`Node`].
     pub fn with_text(self, content: impl AsRef<str>, location: Option<usize>) -> Self {
         self.with_node(Node::text(content), location)
     }
 
-    /// Use the reference to [`Document`] to build all of the renderables within this fragment
+    /// Use the reference to the [`Backend`] to build all of the renderables within this fragment
     /// builder. Returns the constructed fragment.
     pub fn build(
-        self,
-        document: &Document,
-        event_registry: &Rc<RefCell<EventRegistry>>,
-    ) -> Fragment {
-        let mut fragment = Fragment::new(document, event_registry);
-
-        self.nodes
-            .into_iter()
-            .for_each(|NodeBuilder { node, location }| fragment.with_static_node(node, location));
-
-        self.dynamic.into_iter().for_each(
-            |Builder {
-                 dependencies,
-                 location,
-                 builder,
-             }| {
+        mut self,
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<Fragment<B>, Error> {
+        for (location, name, value) in self.attributes {
+            self.nodes
+                .get_mut(location)
+                .ok_or(Error::InvalidLocation(location))?
+                .node
+                .add_attribute(name, value);
+        }
+
+        let mut fragment = Fragment::new(backend, event_registry);
+
+        // Add items in the order they were added to the builder, so that they are mounted in the
+        // same order.
+        let mut nodes = self.nodes.into_iter();
+        let mut dynamic = self.dynamic.into_iter();
+        for is_static in self.order {
+            if is_static {
+                let NodeBuilder { node, location } = nodes.next().expect("static node to exist");
+                fragment.with_static_node(node, location)?;
+            } else {
+                let Builder {
+                    dependencies,
+                    location,
+                    builder,
+                } = dynamic.next().expect("dynamic builder to exist");
                 fragment.with_dynamic(
-                    builder.build(document, event_registry),
+                    builder.build(backend, event_registry)?,
                     &dependencies,
                     location,
                 );
-            },
-        );
+            }
+        }
+
+        Ok(fragment)
+    }
+}
 
-        fragment
+impl<B: Backend, F: Field> Default for FragmentBuilder<B, F> {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,30 +1,40 @@
 mod builder;
 mod util;
 
+use crate::backend::{Backend, WebBackend};
+use crate::component::Field;
 use crate::dynamic::Dynamic;
 use crate::event_registry::EventRegistry;
 use crate::util::HashMapList;
+use crate::Error;
 pub use builder::*;
 pub use util::*;
 
 use std::{cell::RefCell, rc::Rc};
-use web_sys::{Document, Node as WsNode};
 
 /// A top level representation of a fragment. Can contain static data, or iterators of fragments.
 /// Is responsible for mounting/updating/detaching itself and all children. Importantly, it will
 /// only contain static nodes, meaning that there are no variable nodes conditionally being
 /// mounted/unmounted within the fragment. If there are any dynamic nodes, a new fragment must be
 /// created.
-pub struct Fragment {
-    /// A reference to the [`Document`].
-    document: Document,
+pub struct Fragment<B: Backend = WebBackend> {
+    /// A reference to the [`Backend`].
+    backend: B,
 
     /// Collection of all dynamic items (eg [`dynamic::Iterator`]).
-    dynamic: Vec<(Option<usize>, Box<dyn Dynamic>)>,
+    dynamic: Vec<(Option<usize>, Box<dyn Dynamic<B>>)>,
 
-    /// Collection of static [`web_sys::Node`]s, and a reference to the static node that it should
+    /// Collection of static [`Backend::Node`]s, and a reference to the static node that it should
     /// be mounted in.
-    static_nodes: Vec<(Option<usize>, WsNode)>,
+    static_nodes: Vec<(Option<usize>, B::Node)>,
+
+    /// Representation of each of the static nodes, so that existing nodes can be adopted in their
+    /// place when hydrating.
+    static_kinds: Vec<Node>,
+
+    /// The order that static nodes and dynamic items were inserted into the fragment, so that
+    /// siblings are mounted in the same order that they were declared.
+    order: Vec<Part>,
 
     /// Collection mapping between context properties (key), and the dynamic thing that rely on it
     /// (value).
@@ -33,25 +43,29 @@ pub struct Fragment {
     /// Whether the fragment is currently mounted or not.
     mounted: bool,
 
-    event_registry: Rc<RefCell<EventRegistry>>,
+    event_registry: Rc<RefCell<EventRegistry<B>>>,
 }
 
-impl Fragment {
+impl<B: Backend> Fragment<B> {
     /// Create a new [`FragmentBuilder`].
-    pub fn build() -> FragmentBuilder {
+    pub fn build<F: Field>() -> FragmentBuilder<B, F> {
         FragmentBuilder::new()
     }
 
-    /// Create a new fragment. Requires a reference to [`Document`] in order to store for future
-    /// usage, so that [`web_sys::Node`]s can be created as required.
-    pub fn new(document: &Document, event_registry: &Rc<RefCell<EventRegistry>>) -> Self {
+    /// Create a new fragment. Requires a reference to the [`Backend`] in order to store for future
+    /// usage, so that [`Backend::Node`]s can be created as required.
+    pub fn new(backend: &B, event_registry: &Rc<RefCell<EventRegistry<B>>>) -> Self {
         Self {
-            document: document.clone(),
+            backend: backend.clone(),
 
             dynamic: Vec::new(),
 
             static_nodes: Vec::new(),
 
+            static_kinds: Vec::new(),
+
+            order: Vec::new(),
+
             dependencies: HashMapList::new(),
 
             mounted: false,
@@ -61,10 +75,14 @@ impl Fragment {
     }
 
     /// Inserts a static node into the fragment.
-    pub fn with_static_node(&mut self, kind: Node, location: Option<usize>) {
-        let node = kind.create_node(&self.document, &self.event_registry);
+    pub fn with_static_node(&mut self, kind: Node, location: Option<usize>) -> Result<(), Error> {
+        let node = kind.create_node(&self.backend, &self.event_registry)?;
 
+        self.order.push(Part::Static(self.static_nodes.len()));
         self.static_nodes.push((location, node));
+        self.static_kinds.push(kind);
+
+        Ok(())
     }
 
     /// Inserts something implementing [`Dynamic`] into the fragment.
@@ -75,12 +93,13 @@ impl Fragment {
         location: Option<usize>,
     ) -> usize
     where
-        P: 'static + Dynamic,
+        P: 'static + Dynamic<B>,
     {
         let id = self.dynamic.len();
 
+        self.order.push(Part::Dynamic(id));
         self.dynamic
-            .push((location, Box::new(part) as Box<dyn Dynamic>));
+            .push((location, Box::new(part) as Box<dyn Dynamic<B>>));
         self.register_dependencies(id, dependencies);
 
         id
@@ -90,7 +109,7 @@ impl Fragment {
     ///
     /// This uses the [`Dynamic::update()`] method, a generated dependency list based off of
     /// the registered dependencies of [`Dynamic`]s.
-    pub fn full_update(&mut self) {
+    pub fn full_update(&mut self) -> Result<(), Error> {
         Dynamic::update(
             self,
             self.dependencies
@@ -98,7 +117,7 @@ impl Fragment {
                 .cloned()
                 .collect::<Vec<_>>()
                 .as_slice(),
-        );
+        )
     }
 
     /// Helper function to rgister dependencies.
@@ -107,64 +126,136 @@ impl Fragment {
             self.dependencies.insert(*dependency, id);
         }
     }
+
+    /// Helper function to resolve the [`Location`] for something within the fragment, given the
+    /// location that the fragment itself is being mounted to.
+    fn resolve_location(
+        &self,
+        parent_id: Option<usize>,
+        location: &Location<B>,
+    ) -> Result<Location<B>, Error> {
+        match parent_id {
+            Some(parent_id) => self
+                .static_nodes
+                .get(parent_id)
+                .map(|(_, node)| Location::parent(node))
+                .ok_or(Error::InvalidLocation(parent_id)),
+            None => Ok(location.clone()),
+        }
+    }
+}
+
+/// Reference to either a static node or a dynamic item within a [`Fragment`].
+#[derive(Clone, Copy)]
+enum Part {
+    Static(usize),
+    Dynamic(usize),
 }
 
-impl Dynamic for Fragment {
-    fn mount(&mut self, location: &Location) {
-        self.static_nodes.iter().for_each(|(parent_id, node)| {
-            parent_id
-                .map(|parent_id| {
-                    Location::parent(
-                        &self
-                            .static_nodes
-                            .get(parent_id)
-                            .expect("location to exist")
-                            .1,
-                    )
-                })
-                .unwrap_or(location.clone())
-                .mount(node);
-        });
-
-        self.dynamic.iter_mut().for_each(|(parent_id, part)| {
-            part.mount(
-                &parent_id
-                    .map(|parent_id| {
-                        Location::parent(
-                            &self
-                                .static_nodes
-                                .get(parent_id)
-                                .expect("location to exist")
-                                .1,
-                        )
-                    })
-                    .unwrap_or(location.clone()),
-            )
-        });
+impl<B: Backend> Dynamic<B> for Fragment<B> {
+    fn mount(&mut self, location: &Location<B>) -> Result<(), Error> {
+        // Mount in the order that items were inserted, so that static and dynamic siblings keep
+        // their relative positions.
+        for part in self.order.iter().copied() {
+            match part {
+                Part::Static(id) => {
+                    let (parent_id, node) = &self.static_nodes[id];
+                    self.resolve_location(*parent_id, location)?.mount(node)?;
+                }
+                Part::Dynamic(id) => {
+                    let parent_location = self.resolve_location(self.dynamic[id].0, location)?;
+                    self.dynamic[id].1.mount(&parent_location)?;
+                }
+            }
+        }
+
+        self.mounted = true;
+
+        Ok(())
+    }
+
+    fn hydrate(&mut self, cursor: &mut Cursor<B>) -> Result<(), Error> {
+        // Each static node has its own cursor for its children, created once it is adopted
+        let mut cursors: Vec<Cursor<B>> = Vec::with_capacity(self.static_nodes.len());
+
+        for part in self.order.iter().copied() {
+            match part {
+                Part::Static(id) => {
+                    let cursor = match self.static_nodes[id].0 {
+                        Some(parent_id) => cursors
+                            .get_mut(parent_id)
+                            .ok_or(Error::InvalidLocation(parent_id))?,
+                        None => &mut *cursor,
+                    };
+
+                    let node = self.static_kinds[id].hydrate_node(cursor, &self.event_registry)?;
+                    cursors.push(Cursor::new(&self.backend, &node));
+                    self.static_nodes[id].1 = node;
+                }
+                Part::Dynamic(id) => {
+                    let (parent_id, part) = &mut self.dynamic[id];
+                    let cursor = match parent_id {
+                        Some(parent_id) => cursors
+                            .get_mut(*parent_id)
+                            .ok_or(Error::InvalidLocation(*parent_id))?,
+                        None => &mut *cursor,
+                    };
+
+                    part.hydrate(cursor)?;
+                }
+            }
+        }
+
+        // Anything left over within a static node wasn't rendered by this fragment
+        cursors.iter().try_for_each(Cursor::finish)?;
 
         self.mounted = true;
+
+        Ok(())
     }
 
-    fn detach(&mut self, top_level: bool) {
-        self.static_nodes.iter().for_each(|(_, node)| {
-            node.parent_node()
-                .expect("node to have parent")
-                .remove_child(node)
-                .expect("to remove child");
-        });
+    fn detach(&mut self, top_level: bool) -> Result<(), Error> {
+        self.static_nodes
+            .iter()
+            .try_for_each(|(_, node)| B::remove(node))?;
 
         self.dynamic
             .iter_mut()
-            .for_each(|(_, part)| part.detach(top_level));
+            .try_for_each(|(_, part)| part.detach(top_level))?;
 
         self.mounted = false;
+
+        Ok(())
     }
 
-    fn update(&mut self, changed: &[usize]) {
+    fn relocate(&mut self, location: &Location<B>) -> Result<(), Error> {
+        // Only items at the top level of the fragment need to move, as everything else is
+        // contained within one of the static nodes.
+        for part in self.order.iter().copied() {
+            match part {
+                Part::Static(id) => {
+                    if let (None, node) = &self.static_nodes[id] {
+                        location.mount(node)?;
+                    }
+                }
+                Part::Dynamic(id) => {
+                    if let (None, part) = &mut self.dynamic[id] {
+                        part.relocate(location)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, changed: &[usize]) -> Result<(), Error> {
         if self.mounted {
             self.dynamic
                 .iter_mut()
-                .for_each(|(_, part)| part.update(changed));
+                .try_for_each(|(_, part)| part.update(changed))?;
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,250 @@
+use std::fmt::{self, Display};
+
+use crate::backend::{Backend, NodeKind, WebBackend};
+use crate::Error;
+
+/// A mismatch between the nodes that already exist in the DOM, and the nodes that were expected
+/// whilst hydrating.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HydrationError {
+    /// Description of the node that was expected.
+    pub expected: String,
+
+    /// Description of the node that was found instead.
+    pub found: String,
+
+    /// Description of the parent of the mismatched node.
+    pub within: String,
+}
+
+impl Display for HydrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hydration mismatch within {}: expected {}, found {}",
+            self.within, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for HydrationError {}
+
+/// A position within the children of an existing [`Backend::Node`], which is advanced as each
+/// child is adopted whilst hydrating. This is the hydration counterpart to [`super::Location`].
+///
+/// Server rendered markup represents empty text nodes as comments, and separates adjacent text
+/// nodes with a comment (see [`crate::ssr`]). The cursor will consume these comments, replacing
+/// them with text nodes where required.
+pub struct Cursor<B: Backend = WebBackend> {
+    /// A reference to the [`Backend`], used to create text nodes in place of comments.
+    backend: B,
+
+    /// The node whose children are being adopted.
+    parent: B::Node,
+
+    /// The next child to be adopted, or [`None`] if all children have been adopted.
+    next: Option<B::Node>,
+
+    /// Whether the previously adopted node was a text node, meaning that there may be a separator
+    /// before the next text node.
+    previous_text: bool,
+}
+
+impl<B: Backend> Cursor<B> {
+    /// Create a cursor starting at the first child of `parent`.
+    pub fn new(backend: &B, parent: &B::Node) -> Self {
+        Self {
+            backend: backend.clone(),
+            parent: parent.clone(),
+            next: B::first_child(parent),
+            previous_text: false,
+        }
+    }
+
+    /// Get the node whose children are being adopted.
+    pub fn get_parent(&self) -> &B::Node {
+        &self.parent
+    }
+
+    /// Adopt the next node, which must be an element of the provided kind.
+    pub fn take_element(&mut self, kind: &str) -> Result<B::Node, Error> {
+        let node = self
+            .next
+            .clone()
+            .filter(|node| matches!(B::node_kind(node), NodeKind::Element(found) if found == kind))
+            .ok_or_else(|| self.mismatch(format!("element `{kind}`")))?;
+
+        self.advance(false);
+
+        Ok(node)
+    }
+
+    /// Adopt the next node, which must be a text node. If `content` is provided, the content of
+    /// the text node must also match.
+    pub fn take_text(&mut self, content: Option<&str>) -> Result<B::Node, Error> {
+        let expected = || match content {
+            Some(content) => format!("text {content:?}"),
+            None => "text".to_string(),
+        };
+
+        // Skip the separator between adjacent text nodes
+        if self.previous_text && self.next_kind() == Some(NodeKind::Comment) {
+            self.remove_next()?;
+        }
+
+        let node = match self.next_kind() {
+            // An empty text node, which must be re-created as comments aren't rendered as text
+            Some(NodeKind::Comment) if content.unwrap_or_default().is_empty() => {
+                let node = self.backend.create_text("");
+                B::insert_before(&self.parent, &node, self.next.as_ref())?;
+                self.remove_next()?;
+
+                node
+            }
+            Some(NodeKind::Text(found)) if content.is_none_or(|content| content == found) => {
+                let node = self.next.clone().expect("next node to exist");
+                self.advance(true);
+
+                node
+            }
+            _ => return Err(self.mismatch(expected()).into()),
+        };
+
+        self.previous_text = true;
+
+        Ok(node)
+    }
+
+    /// Ensure that every child has been adopted.
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.next {
+            Some(_) => Err(self.mismatch("nothing".to_string()).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Helper function to get the kind of the next node.
+    fn next_kind(&self) -> Option<NodeKind> {
+        self.next.as_ref().map(B::node_kind)
+    }
+
+    /// Helper function to move to the next sibling.
+    fn advance(&mut self, previous_text: bool) {
+        self.next = self.next.as_ref().and_then(B::next_sibling);
+        self.previous_text = previous_text;
+    }
+
+    /// Helper function to remove the next node from the DOM, and move to its sibling.
+    fn remove_next(&mut self) -> Result<(), Error> {
+        if let Some(node) = self.next.take() {
+            self.next = B::next_sibling(&node);
+            B::remove(&node)?;
+        }
+
+        Ok(())
+    }
+
+    /// Helper function to create an error describing the next node.
+    fn mismatch(&self, expected: String) -> HydrationError {
+        HydrationError {
+            expected,
+            found: self
+                .next_kind()
+                .map_or("nothing".to_string(), |kind| describe(&kind)),
+            within: describe(&B::node_kind(&self.parent)),
+        }
+    }
+}
+
+/// Helper function to describe a node for an error.
+fn describe(kind: &NodeKind) -> String {
+    match kind {
+        NodeKind::Element(kind) => format!("element `{kind}`"),
+        NodeKind::Text(content) => format!("text {content:?}"),
+        NodeKind::Comment => "comment".to_string(),
+        NodeKind::Other => "unknown node".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::dynamic::Dynamic;
+    use crate::event_registry::EventRegistry;
+    use crate::fragment::{FragmentBuilder, Location};
+    use crate::html;
+
+    /// Helper function to hydrate `client` from the nodes mounted by `server`, returning the
+    /// number of nodes within the parent afterwards.
+    fn hydrate(
+        server: FragmentBuilder<MemoryBackend>,
+        client: FragmentBuilder<MemoryBackend>,
+    ) -> Result<usize, Error> {
+        let backend = MemoryBackend::new();
+        let parent = backend.body();
+        let event_registry = EventRegistry::new(|_, _| Ok(()), |_| Ok(()));
+
+        server
+            .build(&backend, &event_registry)?
+            .mount(&Location::parent(&parent))?;
+
+        let mut cursor = Cursor::new(&backend, &parent);
+        client
+            .build(&backend, &event_registry)?
+            .hydrate(&mut cursor)?;
+        cursor.finish()?;
+
+        Ok(parent.children().len())
+    }
+
+    #[test]
+    fn matching_nodes_are_adopted() {
+        let nodes = hydrate(
+            html! { <p>"a"</p><span>"b"</span> },
+            html! { <p>"a"</p><span>"b"</span> },
+        )
+        .unwrap();
+
+        assert_eq!(nodes, 2);
+    }
+
+    #[test]
+    fn mismatch_reports_first_differing_node() {
+        let error = hydrate(
+            html! { <p>"a"</p><div /><span /> },
+            html! { <p>"a"</p><span /><div /> },
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Hydration(HydrationError { expected, found, within })
+                if expected == "element `span`"
+                    && found == "element `div`"
+                    && within == "element `body`"
+        ));
+    }
+
+    #[test]
+    fn mismatch_reports_differing_text() {
+        let error = hydrate(html! { <p>"a"</p> }, html! { <p>"b"</p> }).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Hydration(HydrationError { expected, found, within })
+                if expected == "text \"b\"" && found == "text \"a\"" && within == "element `p`"
+        ));
+    }
+
+    #[test]
+    fn extra_nodes_are_reported() {
+        let error = hydrate(html! { <p /><p /> }, html! { <p /> }).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Hydration(HydrationError { expected, found, .. })
+                if expected == "nothing" && found == "element `p`"
+        ));
+    }
+}
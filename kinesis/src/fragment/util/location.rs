@@ -1,64 +1,61 @@
-use web_sys::Node as WsNode;
+use crate::backend::{Backend, WebBackend};
+use crate::Error;
 
-/// Expresses a location relative to a [`web_sys::Node`] in the DOM. Primarily used for directing a
+/// Expresses a location relative to a [`Backend::Node`] in the DOM. Primarily used for directing a
 /// [`super::super::Dynamic`] when mounting it to the DOM.
-#[derive(Clone)]
-pub struct Location {
-    /// The parent to mount the [`web_sys::Node`] within.
-    parent: WsNode,
+pub struct Location<B: Backend = WebBackend> {
+    /// The parent to mount the [`Backend::Node`] within.
+    parent: B::Node,
 
-    /// An optional anchor to use when mounting the [`web_sys::Node`]. If provided, the node will
+    /// An optional anchor to use when mounting the [`Backend::Node`]. If provided, the node will
     /// be inserted before the anchor. If not provided, then the node will be appended to the
     /// parent.
-    anchor: Option<WsNode>,
+    anchor: Option<B::Node>,
 }
 
-impl Location {
+impl<B: Backend> Location<B> {
     /// Create a location from an anchor. Will attempt to retrieve the parent of the anchor, which
     /// will fail if the anchor is not currently mounted.
-    pub fn anchor<N>(anchor: &N) -> Self
-    where
-        N: AsRef<WsNode>,
-    {
-        let anchor = anchor.as_ref();
-
-        Self {
-            parent: anchor.parent_node().expect("anchor to have parent"),
+    pub fn anchor(anchor: &B::Node) -> Result<Self, Error> {
+        Ok(Self {
+            parent: B::parent(anchor).ok_or(Error::MissingParent)?,
             anchor: Some(anchor.clone()),
-        }
+        })
     }
 
     /// Create a location from a parent, without an anchor.
-    pub fn parent<N>(parent: &N) -> Self
-    where
-        N: AsRef<WsNode>,
-    {
+    pub fn parent(parent: &B::Node) -> Self {
         Self {
-            parent: parent.as_ref().clone(),
+            parent: parent.clone(),
             anchor: None,
         }
     }
 
     /// Create a location with both an anchor and a parent.
-    pub fn anchored_parent<P, A>(parent: P, anchor: Option<A>) -> Self
-    where
-        P: AsRef<WsNode>,
-        A: AsRef<WsNode>,
-    {
+    pub fn anchored_parent(parent: &B::Node, anchor: Option<&B::Node>) -> Self {
         Self {
-            parent: parent.as_ref().clone(),
-            anchor: anchor.as_ref().map(|anchor| anchor.as_ref().clone()),
+            parent: parent.clone(),
+            anchor: anchor.cloned(),
         }
     }
 
-    /// Use the location to mount the provided [`web_sys::Node`]. Assumes that the parent is
+    /// Get the parent that nodes will be mounted within.
+    pub fn get_parent(&self) -> &B::Node {
+        &self.parent
+    }
+
+    /// Use the location to mount the provided [`Backend::Node`]. Assumes that the parent is
     /// mounted.
-    pub fn mount<N>(&self, node: &N)
-    where
-        N: AsRef<WsNode>,
-    {
-        self.parent
-            .insert_before(node.as_ref(), self.anchor.as_ref())
-            .expect("node mounted into parent");
+    pub fn mount(&self, node: &B::Node) -> Result<(), Error> {
+        B::insert_before(&self.parent, node, self.anchor.as_ref())
+    }
+}
+
+impl<B: Backend> Clone for Location<B> {
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            anchor: self.anchor.clone(),
+        }
     }
 }
@@ -3,3 +3,6 @@ pub use node::*;
 
 mod location;
 pub use location::*;
+
+mod cursor;
+pub use cursor::*;
@@ -1,30 +1,38 @@
 use std::{cell::RefCell, rc::Rc};
 
-use web_sys::{Document, Node as WsNode};
-
+use super::Cursor;
+use crate::backend::Backend;
+use crate::event::{EventKind, EventOptions, EventType, Handler};
 use crate::fragment::EventRegistry;
+use crate::Error;
 
-/// Information required to build a [`web_sys::Node`]. Offers a friendly interface for creating new
-/// [`web_sys::Node`]s, and allows for programatic access to certain attributes before creation
+/// Information required to build a [`Backend::Node`]. Offers a friendly interface for creating new
+/// [`Backend::Node`]s, and allows for programatic access to certain attributes before creation
 /// (namely whether the node is a text node or an element).
 pub enum NodeType {
-    /// A [`web_sys::Text`] node. Containing [`String`] refers to the content of the generated text
-    /// node, which will be passed to [`Document::create_text_node()`].
+    /// A text node. Containing [`String`] refers to the content of the generated text node, which
+    /// will be passed to [`Backend::create_text()`].
     Text(String),
 
-    /// A [`web_sys::Element`] node. Containing [`String`] refers to the element type (eg `p`,
-    /// `div`), which will be passed to [`Document::create_element()`].
+    /// An element node. Containing [`String`] refers to the element type (eg `p`, `div`), which
+    /// will be passed to [`Backend::create_element()`].
     Element(String),
 }
 
 pub struct Node {
     node_type: NodeType,
-    events: Vec<(String, usize)>,
+
+    /// Events to listen to, along with the id of the event handler to call and the options to
+    /// listen with.
+    events: Vec<(EventType, usize, EventOptions)>,
+
+    /// Attributes (name and value) to set on the element once created. These are ignored for
+    /// text nodes.
+    attributes: Vec<(String, String)>,
 }
 
 impl Node {
-    /// Create a new [`web_sys::Text`] node with the provided content, casting it to a
-    /// [`web_sys::Node`].
+    /// Create a new text node with the provided content.
     pub fn text<S>(content: S) -> Self
     where
         S: AsRef<str>,
@@ -32,11 +40,11 @@ impl Node {
         Self {
             node_type: NodeType::Text(content.as_ref().to_string()),
             events: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
-    /// Create a new [`web_sys::Element`] node of the provided type, casting it to a
-    /// [`web_sys::Node`].
+    /// Create a new element node of the provided type.
     pub fn element<S>(kind: S) -> Self
     where
         S: AsRef<str>,
@@ -44,41 +52,132 @@ impl Node {
         Self {
             node_type: NodeType::Element(kind.as_ref().to_string()),
             events: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
-    pub fn with_event<S>(mut self, event_type: S, event_id: usize) -> Self
+    /// Call `handler` whenever an event of the provided kind is received. The handler must take an
+    /// event which the kind's event can be used as (eg a handler taking an [`crate::event::Event`]
+    /// can be bound to any kind).
+    pub fn with_event<K, E>(self, kind: K, handler: Handler<E>) -> Self
     where
-        S: AsRef<str>,
+        K: EventKind,
+        K::Event: AsRef<E>,
+    {
+        self.with_event_options(kind, handler, EventOptions::default())
+    }
+
+    /// Call `handler` whenever an event of the provided kind is received, as with
+    /// [`Self::with_event()`], listening with the provided options.
+    pub fn with_event_options<K, E>(
+        mut self,
+        _kind: K,
+        handler: Handler<E>,
+        options: EventOptions,
+    ) -> Self
+    where
+        K: EventKind,
+        K::Event: AsRef<E>,
     {
         self.events
-            .push((event_type.as_ref().to_string(), event_id));
+            .push((EventType::of::<K>(), handler.id(), options));
         self
     }
 
-    /// Build a [`web_sys::Node`] based off of the current node representation. Requires a
-    /// reference to [`Document`] in order to call the relevant node creation method on it.
-    pub fn create_node(
+    /// Set an attribute on the element, such as `class` or `href`. The value will be passed to
+    /// [`Backend::set_attribute()`] when the element is created.
+    pub fn with_attribute<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.add_attribute(name, value);
+        self
+    }
+
+    /// Set an attribute on an existing node, as with [`Self::with_attribute()`].
+    pub(crate) fn add_attribute<N, V>(&mut self, name: N, value: V)
+    where
+        N: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.attributes
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+    }
+
+    /// Build a [`Backend::Node`] based off of the current node representation. Requires a
+    /// reference to the [`Backend`] in order to call the relevant node creation method on it.
+    pub fn create_node<B: Backend>(
         &self,
-        document: &Document,
-        event_registry: &Rc<RefCell<EventRegistry>>,
-    ) -> WsNode {
-        let node: WsNode = match &self.node_type {
-            NodeType::Element(element_kind) => document
-                .create_element(element_kind)
-                .expect("to create a new element")
-                .into(),
-            NodeType::Text(text_content) => document.create_text_node(text_content).into(),
+        backend: &B,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<B::Node, Error> {
+        let node = match &self.node_type {
+            NodeType::Element(element_kind) => {
+                let element = backend.create_element(element_kind)?;
+                self.set_attributes(&element, event_registry)?;
+
+                element
+            }
+            NodeType::Text(text_content) => backend.create_text(text_content),
         };
 
-        self.events.iter().for_each(|(event_type, event_id)| {
-            node.add_event_listener_with_callback(
-                event_type,
-                event_registry.borrow_mut().get(*event_id),
-            )
-            .expect("to bind listener");
-        });
+        self.bind_events(&node, event_registry)?;
+
+        Ok(node)
+    }
+
+    /// Adopt an existing [`Backend::Node`] from the `cursor` which matches the current node
+    /// representation, rather than creating a new one. Event listeners will be bound to the
+    /// adopted node.
+    pub fn hydrate_node<B: Backend>(
+        &self,
+        cursor: &mut Cursor<B>,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<B::Node, Error> {
+        let node = match &self.node_type {
+            NodeType::Element(element_kind) => {
+                let element = cursor.take_element(element_kind)?;
+                self.set_attributes(&element, event_registry)?;
+
+                element
+            }
+            NodeType::Text(text_content) => cursor.take_text(Some(text_content))?,
+        };
+
+        self.bind_events(&node, event_registry)?;
+
+        Ok(node)
+    }
+
+    /// Helper function to set each of the attributes on an element, including the attribute to
+    /// scope the component's stylesheet.
+    fn set_attributes<B: Backend>(
+        &self,
+        element: &B::Node,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<(), Error> {
+        for (name, value) in &self.attributes {
+            B::set_attribute(element, name, value)?;
+        }
+
+        if let Some(scope) = event_registry.borrow().get_scope() {
+            B::set_attribute(element, &scope, "")?;
+        }
+
+        Ok(())
+    }
+
+    /// Helper function to listen to each of the events on a node.
+    fn bind_events<B: Backend>(
+        &self,
+        node: &B::Node,
+        event_registry: &Rc<RefCell<EventRegistry<B>>>,
+    ) -> Result<(), Error> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
 
-        node
+        event_registry.borrow_mut().listen(node, &self.events)
     }
 }
@@ -1,12 +1,25 @@
-mod component;
-mod controller;
-mod dynamic;
-mod event_registry;
-mod fragment;
+// Allow the macros to refer to `::kinesis` from within this crate.
+extern crate self as kinesis;
+
+pub mod backend;
+pub mod component;
+pub mod controller;
+pub mod dynamic;
+mod error;
+pub mod event;
+pub mod event_registry;
+pub mod fragment;
+pub mod signal;
+pub mod ssr;
+mod style;
 mod util;
 
 mod simple;
 
+pub use error::Error;
+pub use kinesis_macros::{component, html};
+
+use backend::WebBackend;
 use controller::{Controller, ControllerRef};
 use simple::Simple;
 use wasm_bindgen::prelude::*;
@@ -24,9 +37,15 @@ pub fn main() -> Result<(), JsValue> {
     let body = document.body().expect("body to exist");
 
     let controller_ref = ControllerRef::new();
-    let component = Controller::<Simple>::new(&document, Simple::new(&controller_ref), None);
+    let component = Controller::<Simple>::new(
+        &WebBackend::new(&document),
+        Simple::new(&controller_ref),
+        None,
+    )?;
     controller_ref.replace_with(&component);
-    component.borrow_mut().mount(&Location::parent(&body));
+    component
+        .borrow_mut()
+        .mount(&Location::parent(&body.into()))?;
 
     Ok(())
 }
@@ -0,0 +1,87 @@
+use super::observer::{Observer, Subscribers};
+use crate::Error;
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// Shared state of a [`Memo`].
+struct MemoState<T> {
+    value: RefCell<T>,
+    subscribers: Subscribers,
+
+    /// Observes the signals read whilst computing the value, keeping the subscriptions alive.
+    observer: Rc<Observer>,
+}
+
+/// A value computed from other signals. The value is recomputed as soon as any of the signals that
+/// it read change, however anything that has read the memo is only notified if the computed value
+/// differs from the previous value.
+pub struct Memo<T>(Rc<MemoState<T>>);
+
+impl<T: PartialEq + 'static> Memo<T> {
+    /// Create a new memo, computing its initial value with `compute`.
+    pub fn new<F>(compute: F) -> Self
+    where
+        F: 'static + Fn() -> T,
+    {
+        let compute = Rc::new(compute);
+
+        Self(Rc::new_cyclic(|state: &Weak<MemoState<T>>| {
+            let observer = Rc::new(Observer::new({
+                let state = Weak::clone(state);
+                let compute = Rc::clone(&compute);
+
+                move || match state.upgrade() {
+                    Some(state) => Self(state).recompute(compute.as_ref()),
+                    None => Ok(()),
+                }
+            }));
+
+            MemoState {
+                value: RefCell::new(observer.track(compute.as_ref())),
+                subscribers: Subscribers::default(),
+                observer,
+            }
+        }))
+    }
+
+    /// Get a copy of the value, subscribing the current observer to any future changes.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Run `f` with a reference to the value, subscribing the current observer to any future
+    /// changes.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.subscribers.track();
+        self.with_untracked(f)
+    }
+
+    /// Run `f` with a reference to the value, without subscribing to changes.
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.0.value.borrow())
+    }
+
+    /// Helper function to recompute the value, notifying subscribers only if it changed.
+    fn recompute(&self, compute: &dyn Fn() -> T) -> Result<(), Error> {
+        let value = self.0.observer.track(compute);
+
+        if *self.0.value.borrow() == value {
+            return Ok(());
+        }
+
+        *self.0.value.borrow_mut() = value;
+        self.0.subscribers.notify()
+    }
+}
+
+impl<T> Clone for Memo<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
@@ -0,0 +1,85 @@
+//! Fine-grained reactive state, as an alternative to identifying changed fields with `usize` ids.
+//!
+//! A [`Signal`] holds a value, and records which dynamic items read from it whilst they are
+//! rendered (currently those created with [`crate::fragment::FragmentBuilder::with_iter()`] and
+//! the helpers built upon it, such as `with_updatable` and `with_conditional`). Setting the signal
+//! schedules an update of only those items, without notifying the component's controller. A
+//! [`Memo`] computes a value from other signals, and only notifies its readers when the computed
+//! value changes.
+//!
+//! Signals can be used alongside field ids, so a component can be migrated to them one field at a
+//! time.
+
+mod memo;
+pub(crate) mod observer;
+
+pub use memo::Memo;
+
+use self::observer::Subscribers;
+use crate::Error;
+
+use std::{cell::RefCell, rc::Rc};
+
+/// Shared state of a [`Signal`].
+struct SignalState<T> {
+    value: RefCell<T>,
+    subscribers: Subscribers,
+}
+
+/// A value which notifies everything that has read it whenever it is set. Cloning a signal
+/// produces another reference to the same value.
+pub struct Signal<T>(Rc<SignalState<T>>);
+
+impl<T: 'static> Signal<T> {
+    /// Create a new signal containing `value`.
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(SignalState {
+            value: RefCell::new(value),
+            subscribers: Subscribers::default(),
+        }))
+    }
+
+    /// Get a copy of the value, subscribing the current observer to any future changes.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Run `f` with a reference to the value, subscribing the current observer to any future
+    /// changes.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.subscribers.track();
+        self.with_untracked(f)
+    }
+
+    /// Run `f` with a reference to the value, without subscribing to changes.
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.0.value.borrow())
+    }
+
+    /// Replace the value, notifying everything that has read it.
+    pub fn set(&self, value: T) -> Result<(), Error> {
+        self.update(|current| *current = value)
+    }
+
+    /// Modify the value in place with `f`, notifying everything that has read it.
+    pub fn update(&self, f: impl FnOnce(&mut T)) -> Result<(), Error> {
+        f(&mut self.0.value.borrow_mut());
+
+        self.0.subscribers.notify()
+    }
+}
+
+impl<T: Default + 'static> Default for Signal<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
@@ -0,0 +1,78 @@
+//! Tracking of which [`Observer`] is currently running, so that any signals read whilst it runs
+//! can record it as a subscriber.
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::Error;
+
+/// A function to run when a source that an [`Observer`] has read from changes.
+type NotifyFn = Box<dyn Fn() -> Result<(), Error>>;
+
+thread_local! {
+    /// Observers that are currently running, with the innermost observer last.
+    static OBSERVERS: RefCell<Vec<Rc<Observer>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Something that reacts to changes of the sources (such as a [`super::Signal`]) that it read
+/// from whilst running.
+pub(crate) struct Observer {
+    notify: NotifyFn,
+}
+
+impl Observer {
+    /// Create a new observer, which will call `notify` whenever a source it has read from changes.
+    pub(crate) fn new<F>(notify: F) -> Self
+    where
+        F: 'static + Fn() -> Result<(), Error>,
+    {
+        Self {
+            notify: Box::new(notify),
+        }
+    }
+
+    /// Run `f`, subscribing this observer to any sources that are read within it. Observers may
+    /// be nested, in which case sources are only subscribed to by the innermost observer.
+    pub(crate) fn track<T>(self: &Rc<Self>, f: impl FnOnce() -> T) -> T {
+        OBSERVERS.with(|observers| observers.borrow_mut().push(Rc::clone(self)));
+        let result = f();
+        OBSERVERS.with(|observers| observers.borrow_mut().pop());
+
+        result
+    }
+}
+
+/// The observers subscribed to a source.
+#[derive(Default)]
+pub(crate) struct Subscribers(RefCell<Vec<Weak<Observer>>>);
+
+impl Subscribers {
+    /// Subscribe the currently running observer (if any) to the source.
+    pub(crate) fn track(&self) {
+        let Some(observer) = OBSERVERS.with(|observers| observers.borrow().last().cloned()) else {
+            return;
+        };
+
+        let mut subscribers = self.0.borrow_mut();
+        if !subscribers
+            .iter()
+            .any(|subscriber| subscriber.as_ptr() == Rc::as_ptr(&observer))
+        {
+            subscribers.push(Rc::downgrade(&observer));
+        }
+    }
+
+    /// Notify each subscriber that the source has changed. Subscribers are removed once notified,
+    /// and must read from the source again to re-subscribe. Stops at the first error.
+    pub(crate) fn notify(&self) -> Result<(), Error> {
+        // Take the subscribers before notifying, as they may re-subscribe whilst being notified
+        let subscribers = self.0.take();
+
+        subscribers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .try_for_each(|observer| (observer.notify)())
+    }
+}
@@ -1,196 +1,271 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    component::{Component, ComponentWrapper},
-    controller::{Controller, ControllerRef},
-    fragment::{Fragment, FragmentBuilder, Node},
+    component,
+    component::{ComponentWrapper, Field, Props},
+    controller::{ControllerRef, Spawner},
+    event::MouseEvent,
+    fragment::FragmentBuilder,
+    html,
+    signal::Signal,
 };
-use web_sys::{console, Event};
+use js_sys::Promise;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::console;
 
 pub struct BoldCount(usize);
+
+#[derive(Clone, Copy, Field)]
+pub enum BoldCountField {
+    Count,
+}
+
+#[derive(Props)]
+pub struct BoldCountProps {
+    count: usize,
+}
+
+#[component(fields = BoldCountField, styles = "b { color: rebeccapurple; }")]
 impl BoldCount {
     pub fn new() -> ComponentWrapper<Self> {
         let component = Rc::new(RefCell::new(Self(0)));
 
-        let fragment = Fragment::build()
-            .with_element("p", None)
-            .with_text("Bolded this text: ", Some(0))
-            .with_element("b", Some(0))
-            .with_updatable(&[0], Some(2), {
-                console::log_1(&"updating bold".into());
+        let fragment = html! {
+            <p>
+                {#slot()}
+                <b class="count">
+                    {#dynamic_text(&[BoldCountField::Count], {
+                        let component = Rc::clone(&component);
+                        move || component.borrow().0.to_string()
+                    })}
+                </b>
+            </p>
+            <button on:click={Self::CLEAR}>"Clear"</button>
+        };
 
-                let component = Rc::clone(&component);
-                move || {
-                    let component = component.borrow();
-                    Fragment::build().with_text(component.0.to_string(), None)
-                }
-            })
-            .with_node(Node::element("button").with_event("click", 0), None)
-            .with_text("Clear", Some(3));
+        // A single listener on the parent handles the events of each element
+        ComponentWrapper::new(component, fragment).with_delegated_events()
+    }
 
-        ComponentWrapper::new(component, fragment)
+    #[props]
+    fn set_props(&mut self, props: &BoldCountProps) -> Option<Vec<BoldCountField>> {
+        self.0 = props.count;
+        Some(vec![BoldCountField::Count])
     }
-}
-impl Component for BoldCount {
-    fn handle_event(&mut self, event_id: usize, _event: Event) -> Option<Vec<usize>> {
-        match event_id {
-            0 => {
-                self.0 = 0;
 
-                Some(vec![0])
-            }
-            _ => None,
-        }
+    #[event]
+    fn clear(&mut self) -> Option<Vec<BoldCountField>> {
+        self.0 = 0;
+
+        Some(vec![BoldCountField::Count])
     }
 }
 
+#[derive(Clone, Copy, Field)]
+pub enum SimpleField {
+    Count,
+    Name,
+    IsEven,
+}
+
 #[derive(Default)]
 pub struct Simple {
     count: usize,
+    name: String,
+    show_details: Signal<bool>,
 }
 
+#[component(fields = SimpleField)]
 impl Simple {
     pub fn new(controller_ref: &ControllerRef<Self>) -> ComponentWrapper<Self> {
-        let component_ref = Rc::new(RefCell::new(Self { count: 0 }));
+        let component_ref = Rc::new(RefCell::new(Self::default()));
         let controller_ref = controller_ref.clone();
 
-        let bold_text = BoldCount::new();
+        let bold_text =
+            BoldCount::new().with_default_slot::<SimpleField>(html! { "Bolded this text: " });
         let bold_text_ref = bold_text.clone_component();
 
-        let fragment = Fragment::build()
-            .with_element("p", None)
-            .with_text("some content: ", Some(0))
-            .with_updatable(&[0], Some(0), {
-                let ctx = Rc::clone(&component_ref);
-                move || {
-                    let ctx = ctx.borrow();
-                    Fragment::build().with_text(ctx.count.to_string(), None)
+        let bound_update = {
+            let component = Rc::clone(&component_ref);
+            let bold_text = Rc::clone(&bold_text_ref);
+
+            move |changed: &[BoldCountField]| {
+                let changed = {
+                    let mut component = component.borrow_mut();
+                    let bold_text = bold_text.borrow();
+
+                    changed
+                        .iter()
+                        .map(|changed| match changed {
+                            BoldCountField::Count => {
+                                component.count = bold_text.0;
+                                SimpleField::Count
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                // The change is recorded as originating from the sub-component, so it won't be
+                // passed back down to it.
+                if let Err(error) = controller_ref.notify_changed(&changed) {
+                    console::error_1(&error.into());
+                }
+
+                if changed.is_empty() {
+                    None
+                } else {
+                    Some(changed)
                 }
-            })
-            .with_node(Node::element("button").with_event("click", 0), None)
-            .with_text("decrement", Some(2))
-            .with_node(Node::element("button").with_event("click", 1), None)
-            .with_text("increment", Some(4))
-            .with_conditional(
-                &[0],
-                None,
+            }
+        };
+
+        let fragment = html! {
+            <p>
+                "some content: "
+                {#dynamic_text(&[SimpleField::Count], {
+                    let ctx = Rc::clone(&component_ref);
+                    move || ctx.borrow().count.to_string()
+                })}
+            </p>
+            <button
+                on:click={Self::DECREMENT}
+                disabled={#(&[SimpleField::Count], {
+                    let ctx = Rc::clone(&component_ref);
+                    move || (ctx.borrow().count == 0).then(String::new)
+                })}
+            >
+                "decrement"
+            </button>
+            <button on:click={Self::INCREMENT}>"increment"</button>
+            <button on:click={Self::INCREMENT_LATER}>"increment later"</button>
+            <form on:submit|prevent_default={Self::CLEAR_NAME}>
+                <input bind:value={#(SimpleField::Name, {
+                    let ctx = Rc::clone(&component_ref);
+                    move || ctx.borrow().name.clone()
+                }, {
+                    let ctx = Rc::clone(&component_ref);
+                    move |name| ctx.borrow_mut().name = name
+                })} />
+            </form>
+            <p>
+                "Hello, "
+                {#dynamic_text(&[SimpleField::Name], {
+                    let ctx = Rc::clone(&component_ref);
+                    move || ctx.borrow().name.clone()
+                })}
+            </p>
+            {#conditional(
+                &[SimpleField::IsEven],
+                {
+                    let ctx = Rc::clone(&component_ref);
+                    move || ctx.borrow().is_even()
+                },
+                || html! { <p>"showing!"</p> }
+            )}
+            <button on:click={Self::TOGGLE_DETAILS}>"toggle details"</button>
+            {#conditional(
+                &[],
+                {
+                    let ctx = Rc::clone(&component_ref);
+                    move || ctx.borrow().show_details.get()
+                },
+                || html! { <p>"Only updated when the signal changes"</p> }
+            )}
+            {#await_or_else(
+                &[SimpleField::Count],
                 {
                     let ctx = Rc::clone(&component_ref);
                     move || {
-                        let ctx = ctx.borrow();
-                        ctx.count % 2 == 0
+                        // Resolves on a later tick, standing in for a network request
+                        JsFuture::from(Promise::resolve(&JsValue::from(ctx.borrow().count * 2)))
                     }
                 },
-                || {
-                    Fragment::build()
-                        .with_element("p", None)
-                        .with_text("showing!", Some(0))
+                || html! { <p>"doubling..."</p> },
+                |doubled: JsValue| html! {
+                    <p>{format!("doubled: {}", doubled.as_f64().unwrap_or_default())}</p>
                 },
-            )
-            .with_iter(&[0], None, {
+                |_| html! { <p>"failed to double"</p> }
+            )}
+            {#keyed_iter(&[SimpleField::Count], {
                 let ctx = Rc::clone(&component_ref);
                 move || {
                     let ctx = ctx.borrow();
                     Box::new((0..ctx.count).map(|val| {
-                        Fragment::build()
-                            .with_element("p", None)
-                            .with_text(format!("counting {val}"), Some(0))
-                    })) as Box<dyn Iterator<Item = FragmentBuilder>>
+                        (val, html! { <p>{format!("counting {val}")}</p> })
+                    })) as Box<dyn Iterator<Item = (usize, FragmentBuilder<_, SimpleField>)>>
                 }
-            })
-            .with_component(
-                &[0],
-                None,
-                bold_text,
-                {
-                    let component = Rc::clone(&component_ref);
-                    let bold_text = Rc::clone(&bold_text_ref);
-
-                    move |changed| {
-                        let component = component.borrow();
-                        let mut bold_text = bold_text.borrow_mut();
-
-                        // Update the nested component when the parent's fields change
-                        let changed = changed
-                            .iter()
-                            .filter_map(|changed| match changed {
-                                0 => {
-                                    console::log_1(&"sending data to bold".into());
-
-                                    bold_text.0 = component.count;
-                                    Some(vec![0])
-                                }
-                                _ => None,
-                            })
-                            .flatten()
-                            .collect::<Vec<_>>();
-
-                        if changed.is_empty() {
-                            None
-                        } else {
-                            Some(changed)
-                        }
-                    }
-                },
-                Some({
-                    let component = Rc::clone(&component_ref);
-                    let bold_text = Rc::clone(&bold_text_ref);
-
-                    move |changed: &[usize]| {
-                        console::log_1(&"running bound update".into());
-
-                        let changed = {
-                            let mut component = component.borrow_mut();
-                            let bold_text = bold_text.borrow();
-
-                            changed
-                                .iter()
-                                .filter_map(|changed| match changed {
-                                    0 => {
-                                        console::log_1(&"Updating component from bold".into());
-                                        component.count = bold_text.0;
-                                        Some(vec![0])
-                                    }
-                                    _ => None,
-                                })
-                                .flatten()
-                                .collect::<Vec<_>>()
-                        };
-
-                        // TODO: Somehow prevent this component updating twice. Currently, it
-                        // updates the parent component, notifying the parent component that
-                        // something changed. Since it is bound to the same thing, the sub
-                        // component is then udpated again.
-                        // Either, the sub-component should somehow be freed before it's attempted
-                        // to be updated (need to work out where this is), or need to prevent the
-                        // double update happening (feels like a bad idea).
-                        controller_ref.notify_changed(&changed);
-
-                        if changed.is_empty() {
-                            None
-                        } else {
-                            Some(changed)
-                        }
-                    }
-                }),
-            );
+            })}
+            {#portal(&[SimpleField::Count], "body", html! {
+                <p>
+                    "Rendered through a portal: "
+                    {#dynamic_text(&[SimpleField::Count], {
+                        let ctx = Rc::clone(&component_ref);
+                        move || ctx.borrow().count.to_string()
+                    })}
+                </p>
+            })}
+            {#component(&[SimpleField::Count], bold_text, {
+                let ctx = Rc::clone(&component_ref);
+                move || BoldCountProps { count: ctx.borrow().count }
+            }, Some(bound_update))}
+        };
 
-        ComponentWrapper::new(component_ref, fragment)
+        ComponentWrapper::new(component_ref, fragment).with_derived(
+            SimpleField::IsEven,
+            &[SimpleField::Count],
+            Self::is_even,
+        )
     }
-}
 
-impl Component for Simple {
-    fn handle_event(&mut self, event_id: usize, _event: Event) -> Option<Vec<usize>> {
-        match event_id {
-            0 => {
-                self.count -= 1;
-                Some(vec![0])
-            }
-            1 => {
-                self.count += 1;
-                Some(vec![0])
+    fn is_even(&self) -> bool {
+        self.count.is_multiple_of(2)
+    }
+
+    #[event]
+    fn decrement(&mut self) -> Option<Vec<SimpleField>> {
+        self.count -= 1;
+        Some(vec![SimpleField::Count])
+    }
+
+    #[event]
+    fn increment(&mut self, event: MouseEvent) -> Option<Vec<SimpleField>> {
+        // Holding shift increments faster
+        self.count += if event.shift_key() { 10 } else { 1 };
+        Some(vec![SimpleField::Count])
+    }
+
+    #[event]
+    fn increment_later(&mut self, spawner: Spawner<Self>) {
+        // Resolves on a later tick, standing in for something like a network request
+        let promise = JsFuture::from(Promise::resolve(&JsValue::NULL));
+
+        let result = spawner.spawn(async move {
+            let _ = promise.await;
+
+            |component: &mut Self| {
+                component.count += 1;
+                Some(vec![SimpleField::Count])
             }
-            _ => None,
+        });
+
+        if let Err(error) = result {
+            console::error_1(&error.into());
+        }
+    }
+
+    #[event]
+    fn clear_name(&mut self) -> Option<Vec<SimpleField>> {
+        // Submitting the form would otherwise reload the page
+        self.name.clear();
+        Some(vec![SimpleField::Name])
+    }
+
+    #[event]
+    fn toggle_details(&mut self) {
+        // Signals notify anything that read them, so no fields need to be returned
+        if let Err(error) = self.show_details.update(|show| *show = !*show) {
+            console::error_1(&error.into());
         }
     }
 }
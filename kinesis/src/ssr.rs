@@ -0,0 +1,301 @@
+use crate::backend::{Backend, MemoryBackend, MemoryNode, MemoryNodeKind, PropertyValue};
+use crate::component::{Component, ComponentWrapper, Field};
+use crate::controller::Controller;
+use crate::dynamic::Dynamic;
+use crate::event_registry::EventRegistry;
+use crate::fragment::{FragmentBuilder, Location};
+use crate::style::STYLE_ATTRIBUTE;
+use crate::Error;
+
+use std::iter;
+
+/// Elements which cannot have any children, so must not have a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is not parsed as HTML, so must not be escaped.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// The output of rendering a component, with its stylesheets kept separate from its markup.
+pub struct Rendered {
+    /// The rendered markup.
+    pub html: String,
+
+    /// A `<style>` element for each stylesheet used whilst rendering, which should be placed
+    /// within the `<head>` of the document. When hydrating, these will be re-used rather than
+    /// injected again.
+    pub styles: String,
+}
+
+/// Render a component to a HTML string, without requiring a browser. The component is built with
+/// a [`MemoryBackend`], and every dynamic item is evaluated once as it would be for an initial
+/// mount.
+///
+/// Any stylesheets are discarded, see [`render_component_with_styles()`] to include them.
+pub fn render_component<C>(component: ComponentWrapper<C, MemoryBackend>) -> Result<String, Error>
+where
+    C: Component + ?Sized + 'static,
+{
+    Ok(render_component_with_styles(component)?.html)
+}
+
+/// Render a component as with [`render_component()`], also including the stylesheets of each
+/// component that was rendered.
+pub fn render_component_with_styles<C>(
+    component: ComponentWrapper<C, MemoryBackend>,
+) -> Result<Rendered, Error>
+where
+    C: Component + ?Sized + 'static,
+{
+    // Portal targets are part of the page rather than the rendered markup
+    let backend = MemoryBackend::new().with_detached_fallback();
+    let root = backend.create_element("root")?;
+
+    Controller::new(&backend, component, None)?
+        .borrow()
+        .mount(&Location::parent(&root))?;
+
+    Ok(Rendered {
+        html: render_children(&root),
+        styles: render_styles(&backend),
+    })
+}
+
+/// Render a [`FragmentBuilder`] to a HTML string, without requiring a browser. Event listeners
+/// within the fragment will never be called.
+///
+/// Any stylesheets are discarded, see [`render_fragment_with_styles()`] to include them.
+pub fn render_fragment<F: Field>(
+    builder: FragmentBuilder<MemoryBackend, F>,
+) -> Result<String, Error> {
+    Ok(render_fragment_with_styles(builder)?.html)
+}
+
+/// Render a [`FragmentBuilder`] as with [`render_fragment()`], also including the stylesheets of
+/// each nested component that was rendered.
+pub fn render_fragment_with_styles<F: Field>(
+    builder: FragmentBuilder<MemoryBackend, F>,
+) -> Result<Rendered, Error> {
+    // Portal targets are part of the page rather than the rendered markup
+    let backend = MemoryBackend::new().with_detached_fallback();
+    let root = backend.create_element("root")?;
+
+    let mut fragment = builder.build(&backend, &EventRegistry::new(|_, _| Ok(()), |_| Ok(())))?;
+    fragment.mount(&Location::parent(&root))?;
+    fragment.full_update()?;
+
+    Ok(Rendered {
+        html: render_children(&root),
+        styles: render_styles(&backend),
+    })
+}
+
+/// Serialise each stylesheet that was injected into the backend as a `<style>` element, marked
+/// with its scope so that it isn't injected a second time when hydrating.
+fn render_styles(backend: &MemoryBackend) -> String {
+    backend
+        .styles()
+        .into_iter()
+        .map(|(scope, css)| format!("<style {STYLE_ATTRIBUTE}=\"{scope}\">{css}</style>"))
+        .collect()
+}
+
+/// Serialise all of the children of a node.
+///
+/// Empty text nodes (such as the anchors used by dynamic items) and the boundary between two
+/// adjacent text nodes are written as empty comments, so that a browser parsing the output will
+/// produce the same structure that was rendered, rather than merging or dropping text nodes. This
+/// is relied upon by [`crate::fragment::Cursor`] when hydrating.
+fn render_children(node: &MemoryNode) -> String {
+    let raw = match node.kind() {
+        MemoryNodeKind::Element(kind) => RAW_TEXT_ELEMENTS.contains(&kind.as_str()),
+        MemoryNodeKind::Text(_) => false,
+    };
+
+    let mut html = String::new();
+    let mut previous_text = false;
+
+    for child in node.children() {
+        match child.kind() {
+            MemoryNodeKind::Element(kind) => {
+                html.push_str(&render_element(&kind, &child));
+                previous_text = false;
+            }
+            // Raw text isn't parsed, so comments would become part of the content
+            MemoryNodeKind::Text(content) if raw => html.push_str(&content),
+            MemoryNodeKind::Text(content) => {
+                if previous_text {
+                    html.push_str("<!---->");
+                }
+
+                if content.is_empty() {
+                    html.push_str("<!---->");
+                } else {
+                    html.push_str(&escape(&content, false));
+                }
+
+                previous_text = true;
+            }
+        }
+    }
+
+    html
+}
+
+/// Serialise a single element, including all of its children.
+///
+/// Properties (such as from a [`crate::dynamic::Binding`]) are written as attributes of the same
+/// name, so that form elements have the correct initial state. The exceptions are the `value` of a
+/// `<textarea>`, which is written as its content, and the `value` of a `<select>`, which is written
+/// as `selected` on the matching `<option>`.
+fn render_element(kind: &str, node: &MemoryNode) -> String {
+    let mut attributes = node.attributes();
+
+    if kind == "option"
+        && is_selected(node)
+        && !attributes.iter().any(|(name, _)| name == "selected")
+    {
+        attributes.push(("selected".to_string(), String::new()));
+    }
+
+    for (name, value) in node.properties() {
+        if attributes.iter().any(|(attribute, _)| *attribute == name)
+            || (name == "value" && matches!(kind, "textarea" | "select"))
+        {
+            continue;
+        }
+
+        match value {
+            PropertyValue::String(value) => attributes.push((name, value)),
+            PropertyValue::Bool(true) => attributes.push((name, String::new())),
+            PropertyValue::Bool(false) => (),
+        }
+    }
+
+    let attributes = attributes
+        .into_iter()
+        .map(|(name, value)| format!(" {name}=\"{}\"", escape(&value, true)))
+        .collect::<String>();
+
+    if VOID_ELEMENTS.contains(&kind) {
+        return format!("<{kind}{attributes}>");
+    }
+
+    let content = match property_value(node) {
+        Some(value) if kind == "textarea" => escape(&value, false),
+        _ => render_children(node),
+    };
+
+    format!("<{kind}{attributes}>{content}</{kind}>")
+}
+
+/// Helper function to get the `value` property of an element, if it has been set.
+fn property_value(node: &MemoryNode) -> Option<String> {
+    match MemoryBackend::property(node, "value")? {
+        PropertyValue::String(value) => Some(value),
+        PropertyValue::Bool(_) => None,
+    }
+}
+
+/// Helper function to check whether an `<option>` matches the `value` property of the `<select>`
+/// that it is within.
+fn is_selected(option: &MemoryNode) -> bool {
+    iter::successors(option.parent(), MemoryNode::parent)
+        .find(|node| node.is_element("select"))
+        .and_then(|select| property_value(&select))
+        .is_some_and(|selected| option.option_value() == selected)
+}
+
+/// Escape text so that it can be safely placed within HTML. If `attribute` is set, quotes will
+/// also be escaped so that the text can be placed within a quoted attribute value.
+fn escape(text: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html;
+
+    #[test]
+    fn text_and_attributes_are_escaped() {
+        let html = render_fragment::<usize>(html! {
+            <p title="a < b & \"c\"">"a < b & \"c\""</p>
+            <p>{"<script>"}</p>
+        })
+        .unwrap();
+
+        assert_eq!(
+            html,
+            "<p title=\"a &lt; b &amp; &quot;c&quot;\">a &lt; b &amp; \"c\"</p>\
+             <p>&lt;script&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn adjacent_and_empty_text_are_separated() {
+        let html = render_fragment::<usize>(html! {
+            <p>"a" {"b"} "c"</p>
+            <p>{""}</p>
+            <p>"a" {""} "b"</p>
+        })
+        .unwrap();
+
+        assert_eq!(
+            html,
+            "<p>a<!---->b<!---->c</p><p><!----></p><p>a<!----><!----><!---->b</p>"
+        );
+    }
+
+    #[test]
+    fn textarea_value_is_escaped_content() {
+        let html = render_fragment::<usize>(html! {
+            <textarea bind:value={#(0, || "a < b & \"c\"".to_string(), |_| ())} />
+        })
+        .unwrap();
+
+        assert_eq!(html, "<textarea>a &lt; b &amp; \"c\"</textarea>");
+    }
+
+    #[test]
+    fn select_value_marks_option_as_selected() {
+        let html = render_fragment::<usize>(html! {
+            <select bind:value={#(0, || "b".to_string(), |_| ())}>
+                <option value="a">"A"</option>
+                <option>"b"</option>
+            </select>
+        })
+        .unwrap();
+
+        assert_eq!(
+            html,
+            "<select><option value=\"a\">A</option><option selected=\"\">b</option></select>"
+        );
+    }
+
+    #[test]
+    fn portal_content_is_not_rendered() {
+        let html = render_fragment::<usize>(html! {
+            <p>"before"</p>
+            {#portal(&[], "#modals", html! { <p>"within portal"</p> })}
+            <p>"after"</p>
+        })
+        .unwrap();
+
+        assert_eq!(html, "<p>before</p><p>after</p>");
+    }
+}
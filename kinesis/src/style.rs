@@ -0,0 +1,249 @@
+//! Scoping of component stylesheets. Each stylesheet is identified by a hash of its content, which
+//! is used to build an attribute that is set on every element the component creates. Selectors
+//! within the stylesheet are re-written to require the attribute, so that they only apply to the
+//! component's own elements.
+
+/// Attribute set on injected `<style>` elements, containing the scope of the stylesheet. This is
+/// used to only inject each stylesheet once, including if it was already rendered on the server.
+pub(crate) const STYLE_ATTRIBUTE: &str = "data-kinesis-style";
+
+/// At-rules which contain other rules, so must have their contents scoped.
+const NESTED_AT_RULES: &[&str] = &["media", "supports", "container", "layer"];
+
+/// A stylesheet which has been scoped to a component.
+pub(crate) struct Stylesheet {
+    /// The attribute which is set on each element of the component (eg `data-k-1a2b3c4d`).
+    pub(crate) scope: String,
+
+    /// The re-written stylesheet.
+    pub(crate) css: String,
+}
+
+impl Stylesheet {
+    /// Scope the provided stylesheet. The scope only depends on the content of the stylesheet, so
+    /// it will be the same when rendered on the server and in the browser.
+    pub(crate) fn new(css: &str) -> Self {
+        let scope = format!("data-k-{:08x}", hash(css));
+        let css = scope_rules(&strip_comments(css), &scope);
+
+        Self { scope, css }
+    }
+}
+
+/// Hash the stylesheet with 32 bit FNV-1a, which (unlike [`std::hash::DefaultHasher`]) is stable
+/// across builds.
+fn hash(css: &str) -> u32 {
+    css.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Helper function to remove all comments from a stylesheet.
+fn strip_comments(css: &str) -> String {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+
+    stripped.push_str(rest);
+
+    stripped
+}
+
+/// Helper function to scope a list of rules.
+fn scope_rules(css: &str, scope: &str) -> String {
+    let mut scoped = String::with_capacity(css.len());
+    let mut rest = css.trim_start();
+
+    while !rest.is_empty() {
+        // The prelude is ended by either the start of a block, or the end of a statement
+        let Some(end) = rest.find(['{', ';']) else {
+            scoped.push_str(rest.trim());
+            break;
+        };
+        let prelude = rest[..end].trim();
+
+        if rest[end..].starts_with(';') {
+            scoped.push_str(prelude);
+            scoped.push(';');
+            rest = rest[end + 1..].trim_start();
+            continue;
+        }
+
+        let block_end = closing_brace(rest, end);
+        let block = &rest[end + 1..block_end];
+
+        match prelude.strip_prefix('@') {
+            Some(at_rule) if NESTED_AT_RULES.iter().any(|name| at_rule.starts_with(name)) => {
+                scoped.push_str(&format!("{prelude}{{{}}}", scope_rules(block, scope)));
+            }
+            // Other at-rules (eg `@keyframes`, `@font-face`) don't contain selectors
+            Some(_) => scoped.push_str(&format!("{prelude}{{{}}}", block.trim())),
+            None => scoped.push_str(&format!(
+                "{}{{{}}}",
+                scope_selectors(prelude, scope),
+                block.trim()
+            )),
+        }
+
+        rest = rest.get(block_end + 1..).unwrap_or_default().trim_start();
+    }
+
+    scoped
+}
+
+/// Helper function to find the index of the brace which closes the block opened at `open`. If the
+/// block is never closed, the end of the stylesheet is returned.
+fn closing_brace(css: &str, open: usize) -> usize {
+    let mut depth = 0;
+    let mut quote = None;
+
+    for (index, c) in css.char_indices().skip_while(|(index, _)| *index < open) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    css.len()
+}
+
+/// Helper function to scope each selector in a comma separated list.
+fn scope_selectors(selectors: &str, scope: &str) -> String {
+    split_top_level(selectors, ',')
+        .into_iter()
+        .map(|selector| scope_selector(selector.trim(), scope))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Helper function to add the scope to the last compound selector of a complex selector (eg
+/// `.list > li:hover` becomes `.list > li[scope]:hover`).
+fn scope_selector(selector: &str, scope: &str) -> String {
+    let mut depth = 0;
+    let mut compound_start = 0;
+    let mut insert_at = None;
+
+    for (index, c) in selector.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ' ' | '>' | '+' | '~' if depth == 0 => {
+                compound_start = index + 1;
+                insert_at = None;
+            }
+            // Pseudo classes and elements must come after the attribute
+            ':' if depth == 0 && insert_at.is_none() && index >= compound_start => {
+                insert_at = Some(index);
+            }
+            _ => (),
+        }
+    }
+
+    let insert_at = insert_at.unwrap_or(selector.len());
+
+    format!(
+        "{}[{scope}]{}",
+        &selector[..insert_at],
+        &selector[insert_at..]
+    )
+}
+
+/// Helper function to split on a character, ignoring any within brackets or parentheses.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+
+    parts.push(&text[start..]);
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_selector_scopes_last_compound() {
+        for (selector, expected) in [
+            ("p", "p[s]"),
+            (".list li", ".list li[s]"),
+            // Pseudo classes and elements
+            ("a:hover", "a[s]:hover"),
+            (":hover", "[s]:hover"),
+            ("p::before", "p[s]::before"),
+            ("a:not(.b)::after", "a[s]:not(.b)::after"),
+            // Nested selectors aren't scoped
+            (":is(.a, .b) > p", ":is(.a, .b) > p[s]"),
+            (".list:is(.a p, .b)", ".list[s]:is(.a p, .b)"),
+            ("input[type=\"a b\"]", "input[type=\"a b\"][s]"),
+            // Combinators
+            (".a > .b", ".a > .b[s]"),
+            (".a>.b", ".a>.b[s]"),
+            (".a + .b ~ .c", ".a + .b ~ .c[s]"),
+        ] {
+            assert_eq!(scope_selector(selector, "s"), expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn scope_rules_scopes_selectors_within_rules() {
+        for (css, expected) in [
+            ("a, b:hover { color: red; }", "a[s],b[s]:hover{color: red;}"),
+            (
+                "@import \"other.css\"; p {}",
+                "@import \"other.css\";p[s]{}",
+            ),
+            // Nested at-rules are scoped recursively
+            (
+                "@media (min-width: 1px) { p { color: red; } }",
+                "@media (min-width: 1px){p[s]{color: red;}}",
+            ),
+            (
+                "@supports (display: grid) { @media print { .a .b {} } }",
+                "@supports (display: grid){@media print{.a .b[s]{}}}",
+            ),
+            // Other at-rules don't contain selectors
+            (
+                "@keyframes spin { from { rotate: 0; } to { rotate: 1turn; } }",
+                "@keyframes spin{from { rotate: 0; } to { rotate: 1turn; }}",
+            ),
+            (
+                "@font-face { font-family: \"a { b\"; }",
+                "@font-face{font-family: \"a { b\";}",
+            ),
+        ] {
+            assert_eq!(scope_rules(css, "s"), expected, "{css}");
+        }
+    }
+}
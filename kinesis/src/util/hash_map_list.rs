@@ -27,7 +27,7 @@ where
     /// Inserts a value with a given key into the collection. If there is no existing [Vec] for the
     /// key, an empty one will be initialised before the value is inserted.
     pub fn insert(&mut self, k: K, v: V) {
-        self.0.entry(k).or_insert(Vec::new()).push(v);
+        self.0.entry(k).or_default().push(v);
     }
 
     pub fn iter(&self) -> Iter<'_, K, V> {